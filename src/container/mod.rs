@@ -1,91 +1,826 @@
 use {
+    chrono::Local,
+    regex::Regex,
     crate::{
         traits::GetPath,
-        enums::{ ExitCode, PrintWhich, },
+        enums::{ ExitCode, ListFormat, PrintWhich, StorageMode, },
+        filter::Filter,
+        item::Item,
+        item_holder::{ ItemAction, ItemActor, ItemHolder, },
+        journal::JournalEntry,
         list::List,
+        storage::{ FileStorage, Storage, },
+        print_options::PrintOptions,
+        sort::SortOptions,
     },
     std::{
-        fs::{ File, OpenOptions, },
-        io::{ Write, Read, Error as IOError, },
-        path::PathBuf,
+        collections::hash_map::DefaultHasher,
+        fs::{ self, File, OpenOptions, },
+        hash::{ Hash, Hasher, },
+        io::{ Write, Error as IOError, },
+        path::{ Path, PathBuf, },
     },
 };
 pub struct Container {
     pub path: PathBuf,
-    pub list: List,
+    list: List,
+    pub storage: Box<dyn Storage>,
+    format: ListFormat,
+    pub backup_count: usize,
+    pub mode: StorageMode,
+    pub snapshot_every: usize,
+    pending: Vec<JournalEntry>,
+    journal_len: usize,
+    synced: Option<u64>,
 }
 impl Container {
     pub fn create(ctx: &mut impl GetPath) -> Result<Self, ExitCode> {
-        let path = ctx.get_path_mut();
-        if path.exists() {
-            return Err(ExitCode::FileExists(path.clone()));
-        }
-        { // file creation
-            match File::create(&path) {
-                Ok(_) => {},
-                Err(_) => return Err(ExitCode::FailedToOpen(path.clone())),
-            }
-        } // file unlocked
-        let name = path.file_name().unwrap().to_str().unwrap().to_string();
-        let list = List::new(name);
-        Ok(Self {
-            path: path.clone(),
+        let format = ListFormat::from_path(ctx.get_path());
+        Self::create_as(ctx, format)
+    }
+    pub fn create_as(
+        ctx: &mut impl GetPath, format: ListFormat,
+    ) -> Result<Self, ExitCode> {
+        let path = ctx.get_path_mut().clone();
+        let storage = FileStorage::with_format(path.clone(), format);
+        let mut container = Self::create_with(file_name(&path), Box::new(storage))?;
+        container.set_format(format);
+        Ok(container)
+    }
+    pub fn create_with(
+        name: impl AsRef<str>, mut storage: Box<dyn Storage>,
+    ) -> Result<Self, ExitCode> {
+        let list = storage.create(name.as_ref())?;
+        Ok(Self::new(list, storage))
+    }
+    fn new(list: List, storage: Box<dyn Storage>) -> Self {
+        let path = match storage.file_path() {
+            Some(path) => path.to_path_buf(),
+            None => PathBuf::from(&list.name),
+        };
+        Self {
+            format: ListFormat::from_path(&path),
+            path,
             list,
-        })
+            storage,
+            backup_count: 0,
+            mode: StorageMode::Snapshot,
+            snapshot_every: 0,
+            pending: Vec::new(),
+            journal_len: 0,
+            synced: None,
+        }
     }
     pub fn load(ctx: &mut impl GetPath) -> Result<Self, ExitCode> {
-        let mut path = ctx.get_path_mut().clone();
-        let mut json = String::new();
-        { // file read
-            let mut file = match OpenOptions::new()
-                .read(true)
-                .open(&mut path)
-            {
-                Ok(f) => f,
-                Err(_) => return Err(ExitCode::FailedToOpen(path.clone())),
-            };
-            match file.read_to_string(&mut json) {
-                Ok(_) => {},
-                Err(_) => {
-                    return Err(ExitCode::FailedToRead(path.clone()));
-                },
+        let format = ListFormat::from_path(ctx.get_path());
+        Self::load_as(ctx, format)
+    }
+    pub fn load_as(
+        ctx: &mut impl GetPath, format: ListFormat,
+    ) -> Result<Self, ExitCode> {
+        let path = ctx.get_path_mut().clone();
+        let storage = FileStorage::with_format(path.clone(), format);
+        let mut container = Self::load_with(file_name(&path), Box::new(storage))?;
+        container.set_format(format);
+        Ok(container)
+    }
+    pub fn load_with(
+        name: impl AsRef<str>, storage: Box<dyn Storage>,
+    ) -> Result<Self, ExitCode> {
+        let list = List::new(name.as_ref().to_string());
+        let mut container = Self::new(list, storage);
+        let journaled = container.journal_path()
+            .is_ok_and(|journal| journal.exists());
+        if !journaled {
+            container.list = container.storage.load()?;
+            container.mark_stored();
+        } else {
+            container.mode = StorageMode::Journal;
+            if container.has_snapshot() {
+                container.list = container.storage.load()?;
             }
-        } // file locked
-        let mut list = List::from_json(json)?;
-        list.items.sort_by(|a, b| {
-            a.created.cmp(&b.created)
-        });
-        Ok(Self {
-            path,
-            list,
-        })
+            container.replay()?;
+            container.synced = container.fingerprint();
+        }
+        if container.list.ensure_ids() {
+            container.synced = None;
+        }
+        Ok(container)
+    }
+    pub fn list(&self) -> &List {
+        &self.list
+    }
+    pub fn list_mut(&mut self) -> &mut List {
+        self.synced = None;
+        &mut self.list
+    }
+    pub fn needs_compaction(&self) -> bool {
+        self.mode.eq(&StorageMode::Journal) && (!self.has_snapshot() || !self.is_synced())
+    }
+    pub fn format(&self) -> ListFormat {
+        self.format
+    }
+    pub fn set_format(&mut self, format: ListFormat) {
+        if let Some(path) = self.storage.file_path() {
+            self.storage = Box::new(FileStorage::with_format(path, format));
+        }
+        self.format = format;
+    }
+    fn has_snapshot(&self) -> bool {
+        match self.storage.file_path() {
+            Some(path) => fs::metadata(path).is_ok_and(|metadata| metadata.len() > 0),
+            None => self.storage.exists(),
+        }
+    }
+    fn file_path(&self, feature: &str) -> Result<&Path, ExitCode> {
+        match self.storage.file_path() {
+            Some(path) => Ok(path),
+            None => Err(ExitCode::UnsupportedStorage(feature.to_string())),
+        }
     }
     pub fn save(&mut self) -> Result<(), ExitCode> {
-        let json = self.list.to_json()?;
-        { // file open:write
-            let bytes = json.as_bytes();
+        match self.mode {
+            StorageMode::Snapshot => {
+                if self.storage.applies_actions() && self.is_synced() {
+                    return Ok(());
+                }
+                self.write_snapshot()?;
+                if let Ok(journal) = self.journal_path() {
+                    if journal.exists() && fs::remove_file(&journal).is_err() {
+                        return Err(ExitCode::FailedToWrite(journal));
+                    }
+                }
+                self.journal_len = 0;
+                self.pending.clear();
+                self.mark_stored();
+                Ok(())
+            },
+            StorageMode::Journal => {
+                self.journal_path()?;
+                if !self.has_snapshot() || !self.is_synced() {
+                    return self.compact();
+                }
+                self.append_journal()?;
+                if self.snapshot_every > 0 && self.journal_len >= self.snapshot_every {
+                    self.compact()?;
+                }
+                Ok(())
+            },
+        }
+    }
+    fn write_snapshot(&mut self) -> Result<(), ExitCode> {
+        if self.backup_count > 0 {
+            self.backup()?;
+            self.prune_backups()?;
+        }
+        self.storage.save(&self.list)
+    }
+    fn persist(&mut self) -> Result<(), ExitCode> {
+        match self.mode {
+            StorageMode::Snapshot => self.save(),
+            StorageMode::Journal => self.compact(),
+        }
+    }
+    pub fn journal_path(&self) -> Result<PathBuf, ExitCode> {
+        let path = self.file_path("Journal mode")?;
+        Ok(path.with_file_name(format!("{}.journal", file_name(path))))
+    }
+    fn fingerprint(&self) -> Option<u64> {
+        let json = self.list.to_json().ok()?;
+        let mut hasher = DefaultHasher::new();
+        json.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+    fn is_synced(&self) -> bool {
+        self.synced.is_some() && self.synced.eq(&self.fingerprint())
+    }
+    fn mark_stored(&mut self) {
+        self.synced = match self.storage.applies_actions() {
+            true => self.fingerprint(),
+            false => None,
+        };
+    }
+    fn replaces_item(&self, path: &[usize], action: &ItemAction) -> bool {
+        match action {
+            ItemAction::StartTimer(_) | ItemAction::StopTimer => true,
+            ItemAction::AlterStatus(_)
+            | ItemAction::CycleStatus
+            | ItemAction::CycleStatusIn(_) => {
+                self.list.get_item(path).is_some_and(|item| item.recurrence.is_some())
+            },
+            _ => false,
+        }
+    }
+    fn append_journal(&mut self) -> Result<(), ExitCode> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for entry in self.pending.iter() {
+            lines.push_str(&entry.to_line()?);
+        }
+        let journal = self.journal_path()?;
+        { // file open:append
             let mut file = match OpenOptions::new()
-                .truncate(true)
-                .write(true)
-                .open(&self.path)
+                .create(true)
+                .append(true)
+                .open(&journal)
             {
                 Ok(f) => f,
-                Err(_) => return Err(ExitCode::FailedToOpen(self.path.clone())),
+                Err(_) => return Err(ExitCode::FailedToOpen(journal)),
             };
-            match file.write_all(bytes) {
+            match file.write_all(lines.as_bytes()) {
                 Ok(_) => {},
-                Err(_) => return Err(ExitCode::FailedToWrite(self.path.clone())),
+                Err(_) => return Err(ExitCode::FailedToWrite(journal)),
             }
         } // file locked
+        self.journal_len += self.pending.len();
+        self.pending.clear();
+        Ok(())
+    }
+    pub fn read_journal(&self) -> Result<Vec<JournalEntry>, ExitCode> {
+        let journal = self.journal_path()?;
+        if !journal.exists() {
+            return Ok(Vec::new());
+        }
+        let content = match fs::read_to_string(&journal) {
+            Ok(content) => content,
+            Err(_) => return Err(ExitCode::FailedToRead(journal)),
+        };
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(JournalEntry::from_line(line)?);
+        }
+        Ok(entries)
+    }
+    fn replay(&mut self) -> Result<(), ExitCode> {
+        let entries = self.read_journal()?;
+        self.journal_len = entries.len();
+        for entry in entries.into_iter() {
+            let mut indices = entry.path.iter().rev().copied().collect::<Vec<usize>>();
+            self.list.try_act_on_item_at(&mut indices, entry.action.clone())?;
+            self.list.stamp_path(entry.stamped_path(), entry.timestamp);
+        }
+        Ok(())
+    }
+    pub fn compact(&mut self) -> Result<(), ExitCode> {
+        let journal = self.journal_path()?;
+        self.pending.clear();
+        self.write_snapshot()?;
+        match File::create(&journal) {
+            Ok(_) => {},
+            Err(_) => return Err(ExitCode::FailedToWrite(journal)),
+        }
+        self.journal_len = 0;
+        self.synced = self.fingerprint();
         Ok(())
     }
+    pub fn backup_dir(&self) -> Result<PathBuf, ExitCode> {
+        Ok(self.file_path("Backups")?.with_file_name(".todo-backups"))
+    }
+    fn backup_pattern(&self) -> Result<Regex, ExitCode> {
+        let name = file_name(self.file_path("Backups")?);
+        Ok(Regex::new(&format!(
+            r"^{}\.(\d{{8}}-\d{{6}}-\d{{3}})(?:-(\d+))?$",
+            regex::escape(&name),
+        )).unwrap())
+    }
+    pub fn backup(&self) -> Result<Option<PathBuf>, ExitCode> {
+        let path = self.file_path("Backups")?;
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.len() > 0 => {},
+            _ => return Ok(None),
+        }
+        let dir = self.backup_dir()?;
+        if fs::create_dir_all(&dir).is_err() {
+            return Err(ExitCode::FailedToWrite(dir));
+        }
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+        let name = file_name(path);
+        let mut backup = dir.join(format!("{}.{}", name, timestamp));
+        let mut copy = 1;
+        while backup.exists() {
+            backup = dir.join(format!("{}.{}-{}", name, timestamp, copy));
+            copy += 1;
+        }
+        match fs::copy(path, &backup) {
+            Ok(_) => Ok(Some(backup)),
+            Err(_) => Err(ExitCode::FailedToWrite(backup)),
+        }
+    }
+    pub fn list_backups(&self) -> Result<Vec<PathBuf>, ExitCode> {
+        let dir = self.backup_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => return Err(ExitCode::FailedToRead(dir)),
+        };
+        let pattern = self.backup_pattern()?;
+        let mut backups = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                let captures = pattern.captures(&name)?;
+                let copy = captures.get(2)
+                    .and_then(|copy| copy.as_str().parse::<usize>().ok())
+                    .unwrap_or(0);
+                Some(((captures[1].to_string(), copy), path))
+            })
+            .collect::<Vec<((String, usize), PathBuf)>>();
+        backups.sort_by(|a, b| b.0.cmp(&a.0));
+        Ok(backups.into_iter().map(|(_, path)| path).collect())
+    }
+    fn prune_backups(&self) -> Result<(), ExitCode> {
+        for backup in self.list_backups()?.into_iter().skip(self.backup_count) {
+            if fs::remove_file(&backup).is_err() {
+                return Err(ExitCode::FailedToWrite(backup));
+            }
+        }
+        Ok(())
+    }
+    pub fn restore_backup(&mut self, backup: &Path) -> Result<(), ExitCode> {
+        self.list = FileStorage::with_format(backup, self.format).load()?;
+        self.persist()
+    }
+    pub fn archive_path(&self) -> Result<PathBuf, ExitCode> {
+        let path = self.file_path("Archiving")?;
+        let stem = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => self.list.name.clone(),
+        };
+        Ok(path.with_file_name(format!("{}.archive.{}", stem, self.format.extension())))
+    }
+    pub fn load_archive(&self) -> Result<List, ExitCode> {
+        let path = self.archive_path()?;
+        if !path.exists() {
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            return Ok(List::new(name));
+        }
+        FileStorage::with_format(path, self.format).load()
+    }
+    fn write_archive(&self, archive: &List) -> Result<(), ExitCode> {
+        let mut storage = FileStorage::with_format(self.archive_path()?, self.format);
+        if !storage.exists() {
+            storage.create(&archive.name)?;
+        }
+        storage.save(archive)
+    }
+    fn rollback_archive(&self, original: Option<&List>) {
+        match original {
+            Some(archive) => {
+                let _ = self.write_archive(archive);
+            },
+            None => {
+                if let Ok(path) = self.archive_path() {
+                    let _ = fs::remove_file(path);
+                }
+            },
+        }
+    }
+    fn commit_archive(
+        &mut self, archive: &List, original: Option<&List>, previous: List,
+    ) -> Result<(), ExitCode> {
+        if let Err(e) = self.write_archive(archive) {
+            self.list = previous;
+            return Err(e);
+        }
+        if let Err(e) = self.persist() {
+            self.list = previous;
+            self.rollback_archive(original);
+            return Err(e);
+        }
+        Ok(())
+    }
+    pub fn archive(&mut self, older_than_days: Option<u32>) -> Result<usize, ExitCode> {
+        let previous = self.list.clone();
+        let items = self.list.take_archivable(older_than_days);
+        let count = items.len();
+        if count.eq(&0) {
+            return Ok(0);
+        }
+        let mut archive = match self.load_archive() {
+            Ok(archive) => archive,
+            Err(e) => {
+                self.list = previous;
+                return Err(e);
+            },
+        };
+        let original = self.archive_path()?.exists().then(|| archive.clone());
+        archive.items.extend(items);
+        archive.last_updated = Local::now();
+        self.commit_archive(&archive, original.as_ref(), previous)?;
+        Ok(count)
+    }
+    pub fn restore(&mut self, filter: &Filter) -> Result<usize, ExitCode> {
+        let mut archive = self.load_archive()?;
+        let original = archive.clone();
+        let context = self.list.filter_context();
+        let (restored, kept) = archive.items.into_iter()
+            .partition::<Vec<Item>, _>(|item| filter.matches_in(item, &context));
+        let count = restored.len();
+        if count.eq(&0) {
+            return Ok(0);
+        }
+        archive.items = kept;
+        archive.last_updated = Local::now();
+        let previous = self.list.clone();
+        self.list.restore_items(restored);
+        self.commit_archive(&archive, Some(&original), previous)?;
+        Ok(count)
+    }
     pub fn print(
         &mut self, output: &mut String, print_which: &PrintWhich, plain: bool,
         max_level: Option<usize>, display_hidden: bool,
     ) -> Result<(), IOError> {
         self.list.print(output, print_which, plain, max_level, display_hidden)
     }
+    pub fn print_filtered(
+        &mut self, output: &mut String, filter: &Filter, plain: bool,
+        max_level: Option<usize>, display_hidden: bool,
+    ) -> Result<(), IOError> {
+        self.list.print_filtered(output, filter, plain, max_level, display_hidden)
+    }
+    pub fn print_with(
+        &mut self, output: &mut String, options: &PrintOptions,
+    ) -> Result<(), IOError> {
+        self.list.print_with(output, options)
+    }
+    pub fn sort(&mut self, options: &SortOptions) -> Result<(), ExitCode> {
+        self.list.sort(options);
+        self.persist()
+    }
     pub fn status(&mut self, content: &mut String, print_which: &PrintWhich) {
         self.list.status(content, print_which);
     }
+    pub fn status_filtered(&mut self, content: &mut String, filter: &Filter) {
+        self.list.status_filtered(content, filter);
+    }
+    pub fn time_report(&self, output: &mut String, plain: bool) {
+        self.list.time_report(output, plain);
+    }
+}
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => path.to_string_lossy().to_string(),
+    }
+}
+impl Container {
+    fn act(
+        &mut self, indices: &mut Vec<usize>, action: ItemAction, validate: bool,
+    ) -> Result<Option<Item>, ExitCode> {
+        if self.mode.eq(&StorageMode::Snapshot) {
+            if !self.storage.applies_actions() {
+                return match validate {
+                    true => self.list.try_act_on_item_at(indices, action),
+                    false => Ok(self.list.act_on_item_at(indices, action)),
+                };
+            }
+            let action = match validate {
+                true => self.list.resolve_action(indices, action)?,
+                false => self.list.prepare_action(indices, action),
+            };
+            let synced = self.is_synced();
+            let path = indices.iter().rev().copied().collect::<Vec<usize>>();
+            let out_item = match validate {
+                true => self.list.try_act_on_item_at(indices, action.clone())?,
+                false => self.list.act_on_item_at(indices, action.clone()),
+            };
+            self.synced = None;
+            if synced {
+                match self.storage.apply_action(&self.list, &path, &action) {
+                    Ok(_) => self.synced = self.fingerprint(),
+                    Err(e) if validate => return Err(e),
+                    Err(_) => {},
+                }
+            }
+            return Ok(out_item);
+        }
+        let action = match validate {
+            true => self.list.resolve_action(indices, action)?,
+            false => self.list.prepare_action(indices, action),
+        };
+        let action = match action {
+            ItemAction::Add(item_type, message) => {
+                ItemAction::Put(Box::new(Item::new(item_type, message)))
+            },
+            action => action,
+        };
+        let synced = self.is_synced();
+        let path = indices.iter().rev().copied().collect::<Vec<usize>>();
+        let replaces = self.replaces_item(&path, &action);
+        let mut entry = JournalEntry::new(path, action.clone());
+        let out_item = match validate {
+            true => self.list.try_act_on_item_at(indices, action)?,
+            false => self.list.act_on_item_at(indices, action),
+        };
+        self.list.stamp_path(entry.stamped_path(), entry.timestamp);
+        if !synced {
+            self.synced = None;
+            return Ok(out_item);
+        }
+        if replaces {
+            match self.list.get_item(&entry.path) {
+                Some(item) => entry.action = ItemAction::Replace(Box::new(item.clone())),
+                None => {
+                    self.synced = None;
+                    return Ok(out_item);
+                },
+            }
+        }
+        self.pending.push(entry);
+        self.synced = self.fingerprint();
+        Ok(out_item)
+    }
+}
+impl ItemActor for Container {
+    fn try_act_on_item_at(
+        &mut self, indices: &mut Vec<usize>, action: ItemAction
+    ) -> Result<Option<Item>, ExitCode> {
+        self.act(indices, action, true)
+    }
+    fn act_on_item_at(
+        &mut self, indices: &mut Vec<usize>, action: ItemAction
+    ) -> Option<Item> {
+        self.act(indices, action, false).unwrap_or(None)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            enums::{ ItemRef, ItemStatus, ItemType, Recurrence, SortKey, },
+            json_style::JsonStyle,
+            scanner::{ Scan, TodoComment, },
+            storage::MemoryStorage,
+            test_utils::{ temp_dir, texts, },
+        },
+    };
+    struct Ctx {
+        path: PathBuf,
+    }
+    impl GetPath for Ctx {
+        fn get_path(&self) -> &PathBuf {
+            &self.path
+        }
+        fn get_path_mut(&mut self) -> &mut PathBuf {
+            &mut self.path
+        }
+    }
+    fn temp_ctx(test: &str, file: &str) -> Ctx {
+        Ctx { path: temp_dir(test).join(file), }
+    }
+    #[test]
+    fn sort_persists_the_new_order() {
+        let mut ctx = temp_ctx("sort", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        for text in ["pear", "apple", "fig"] {
+            container.list_mut().items.push(Item::new(ItemType::Todo, text));
+        }
+        container.save().unwrap();
+        container.sort(&SortOptions::new(SortKey::Text, false, false)).unwrap();
+        let reloaded = Container::load(&mut ctx).unwrap();
+        assert_eq!(texts(&reloaded.list().items), vec!["apple", "fig", "pear"]);
+    }
+    #[test]
+    fn legacy_lists_get_ids_in_memory_and_persist_them_on_save() {
+        let mut ctx = temp_ctx("legacy-ids", "list.json");
+        let legacy = r#"{"name":"list.json","items":[{"item_type":"Todo",
+            "status":"Incomplete","text":"old","sub_items":[],
+            "created":"2024-01-01T00:00:00+00:00",
+            "last_updated":"2024-01-01T00:00:00+00:00","hidden":false}],
+            "created":"2024-01-01T00:00:00+00:00",
+            "last_updated":"2024-01-01T00:00:00+00:00"}"#;
+        fs::write(ctx.get_path(), legacy).unwrap();
+        let mut first = Container::load(&mut ctx).unwrap();
+        let id = first.list().items[0].id.clone();
+        assert!(!id.is_empty());
+        assert_eq!(fs::read_to_string(ctx.get_path()).unwrap(), legacy);
+        first.save().unwrap();
+        let second = Container::load(&mut ctx).unwrap();
+        assert_eq!(second.list().items[0].id, id);
+        fs::write(ctx.get_path(), legacy).unwrap();
+        fs::write(first.journal_path().unwrap(), "").unwrap();
+        let mut journaled = Container::load(&mut ctx).unwrap();
+        assert!(journaled.mode.eq(&StorageMode::Journal));
+        assert_eq!(fs::read_to_string(ctx.get_path()).unwrap(), legacy);
+        journaled.save().unwrap();
+        let third = Container::load(&mut ctx).unwrap();
+        assert_eq!(third.list().items[0].id, journaled.list().items[0].id);
+    }
+    #[test]
+    fn archive_and_restore_follow_the_parent_id() {
+        let mut ctx = temp_ctx("archive", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        let mut parent = Item::new(ItemType::Todo, "parent");
+        let mut done = Item::new(ItemType::Todo, "done");
+        done.status = crate::enums::ItemStatus::Complete;
+        parent.sub_items.push(Item::new(ItemType::Todo, "open"));
+        parent.sub_items.push(done);
+        let mut top = Item::new(ItemType::Todo, "b-top");
+        top.status = crate::enums::ItemStatus::Complete;
+        container.list_mut().items.push(Item::new(ItemType::Todo, "another"));
+        container.list_mut().items.push(parent);
+        container.list_mut().items.push(top);
+        container.save().unwrap();
+        assert_eq!(container.archive(Some(0)).unwrap(), 2);
+        assert!(container.archive_path().unwrap().exists());
+        assert_eq!(container.load_archive().unwrap().items.len(), 2);
+        container.sort(&SortOptions::new(SortKey::Text, true, false)).unwrap();
+        assert_eq!(texts(&container.list().items), vec!["parent", "another"]);
+        assert_eq!(container.restore(&Filter::All).unwrap(), 2);
+        let reloaded = Container::load(&mut ctx).unwrap();
+        assert_eq!(texts(&reloaded.list().items), vec!["parent", "another", "b-top"]);
+        assert_eq!(reloaded.list().items[0].sub_items[1].text, "done");
+        assert!(reloaded.list().items[0].sub_items[1].archived_parent.is_none());
+        assert!(reloaded.load_archive().unwrap().items.is_empty());
+    }
+    #[test]
+    fn failed_list_write_rolls_the_archive_back() {
+        let mut ctx = temp_ctx("archive-rollback", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        let mut done = Item::new(ItemType::Todo, "done");
+        done.status = crate::enums::ItemStatus::Complete;
+        container.list_mut().items.push(done);
+        container.save().unwrap();
+        fs::remove_file(ctx.get_path()).unwrap();
+        assert!(matches!(container.archive(None), Err(ExitCode::FailedToOpen(_))));
+        assert!(!container.archive_path().unwrap().exists());
+        assert_eq!(texts(&container.list().items), vec!["done"]);
+    }
+    #[test]
+    fn pruning_keeps_the_newest_backups_of_this_list_only() {
+        let mut ctx = temp_ctx("backups", "a.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.backup_count = 2;
+        let dir = container.backup_dir().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        let other = dir.join("a.json.old.20240101-000000-000");
+        let unrelated = dir.join("a.json.notes");
+        fs::write(&other, "{}").unwrap();
+        fs::write(&unrelated, "{}").unwrap();
+        for i in 0..4 {
+            container.list_mut().items.push(Item::new(ItemType::Todo, format!("item {}", i)));
+            container.save().unwrap();
+        }
+        let backups = container.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(other.exists() && unrelated.exists());
+        let newest = FileStorage::with_format(&backups[0], container.format()).load().unwrap();
+        assert_eq!(newest.items.len(), 3);
+    }
+    #[test]
+    fn restore_backup_replaces_the_list() {
+        let mut ctx = temp_ctx("restore-backup", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.backup_count = 3;
+        container.list_mut().items.push(Item::new(ItemType::Todo, "first"));
+        container.save().unwrap();
+        container.list_mut().items.push(Item::new(ItemType::Todo, "second"));
+        container.save().unwrap();
+        let backup = container.list_backups().unwrap().remove(0);
+        container.restore_backup(&backup).unwrap();
+        let reloaded = Container::load(&mut ctx).unwrap();
+        assert_eq!(texts(&reloaded.list().items), vec!["first"]);
+    }
+    fn journal_container(test: &str) -> (Ctx, Container) {
+        let mut ctx = temp_ctx(test, "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.mode = StorageMode::Journal;
+        for text in ["pear", "apple", "fig"] {
+            container.list_mut().items.push(Item::new(ItemType::Todo, text));
+        }
+        container.save().unwrap();
+        (ctx, container)
+    }
+    fn assert_reloads(ctx: &mut Ctx, container: &mut Container) {
+        container.save().unwrap();
+        let reloaded = Container::load(ctx).unwrap();
+        assert_eq!(reloaded.list().to_json().unwrap(), container.list().to_json().unwrap());
+    }
+    #[test]
+    fn journal_replays_container_actions() {
+        let (mut ctx, mut container) = journal_container("journal-actions");
+        container.list_mut().items[1].recurrence = Some(Recurrence::Daily);
+        container.save().unwrap();
+        let actions = [
+            (vec![], ItemAction::Add(ItemType::Todo, "seed".to_string())),
+            (vec![1], ItemAction::AlterStatus(ItemStatus::InProgress)),
+            (vec![3], ItemAction::StartTimer(Some("focus".to_string()))),
+            (vec![3], ItemAction::StopTimer),
+            (vec![2], ItemAction::AlterStatus(ItemStatus::Complete)),
+            (vec![2], ItemAction::CycleStatus),
+            (vec![4], ItemAction::Remove),
+        ];
+        for (mut indices, action) in actions.into_iter() {
+            container.try_act_on_item_at(&mut indices, action).unwrap();
+        }
+        container.save().unwrap();
+        assert_eq!(container.read_journal().unwrap().len(), 7);
+        assert_eq!(container.list().items[1].occurrences.len(), 2);
+        assert_reloads(&mut ctx, &mut container);
+    }
+    #[test]
+    fn journal_compacts_after_list_level_edits() {
+        let (mut ctx, mut container) = journal_container("journal-list");
+        assert!(!container.needs_compaction());
+        container.list_mut().act_on_item_at(&mut vec![1], ItemAction::Edit("plum".to_string()));
+        assert!(container.needs_compaction());
+        assert_reloads(&mut ctx, &mut container);
+        assert!(container.read_journal().unwrap().is_empty());
+        assert!(!container.needs_compaction());
+        let item = ItemRef::Id(container.list().items[0].id.clone());
+        let prerequisite = ItemRef::Id(container.list().items[1].id.clone());
+        container.list_mut().add_dependency(&item, &prerequisite).unwrap();
+        assert!(container.needs_compaction());
+        assert_reloads(&mut ctx, &mut container);
+        container.list_mut().act_on_matching(&Filter::All, ItemAction::AddTag("t".to_string()))
+            .unwrap();
+        assert_reloads(&mut ctx, &mut container);
+        container.list_mut().sort(&SortOptions::new(SortKey::Text, false, false));
+        assert_reloads(&mut ctx, &mut container);
+        container.sort(&SortOptions::new(SortKey::Text, true, false)).unwrap();
+        assert_reloads(&mut ctx, &mut container);
+        container.list_mut().sync_comments(&Scan {
+            files: vec!["src/main.rs".to_string()],
+            comments: vec![TodoComment {
+                file: "src/main.rs".to_string(),
+                line: 3,
+                marker: "TODO".to_string(),
+                text: "scanned".to_string(),
+            }],
+        });
+        assert_reloads(&mut ctx, &mut container);
+        container.try_act_on_item_at(&mut vec![1], ItemAction::ToggleHidden).unwrap();
+        assert!(!container.needs_compaction());
+        assert_reloads(&mut ctx, &mut container);
+        assert_eq!(container.read_journal().unwrap().len(), 1);
+    }
+    #[test]
+    fn switching_to_journal_keeps_earlier_edits() {
+        let mut ctx = temp_ctx("journal-switch", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.list_mut().items.push(Item::new(ItemType::Todo, "before"));
+        container.mode = StorageMode::Journal;
+        container.try_act_on_item_at(&mut vec![1], ItemAction::Edit("after".to_string()))
+            .unwrap();
+        assert_reloads(&mut ctx, &mut container);
+        assert_eq!(texts(&container.list().items), vec!["after"]);
+    }
+    #[test]
+    fn set_format_changes_how_the_list_is_saved() {
+        let mut ctx = temp_ctx("set-format", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.list_mut().items.push(Item::new(ItemType::Todo, "first"));
+        container.set_format(ListFormat::Yaml);
+        container.save().unwrap();
+        assert!(fs::read_to_string(ctx.get_path()).unwrap().contains("text: first"));
+        let reloaded = Container::load_as(&mut ctx, ListFormat::Yaml).unwrap();
+        assert_eq!(reloaded.format(), ListFormat::Yaml);
+        assert_eq!(texts(&reloaded.list().items), vec!["first"]);
+    }
+    #[test]
+    fn pretty_json_style_is_used_when_saving() {
+        let mut ctx = temp_ctx("json-style", "list.json");
+        let mut container = Container::create(&mut ctx).unwrap();
+        container.list_mut().items.push(Item::new(ItemType::Todo, "first"));
+        container.set_format(ListFormat::Json(JsonStyle::pretty()));
+        container.save().unwrap();
+        let json = fs::read_to_string(ctx.get_path()).unwrap();
+        assert!(json.contains("\n  \"items\": [\n") && json.ends_with("}\n"));
+        assert_eq!(texts(&Container::load(&mut ctx).unwrap().list().items), vec!["first"]);
+    }
+    #[test]
+    fn memory_storage_rejects_file_only_features() {
+        let mut container = Container::create_with(
+            "memory-list", Box::new(MemoryStorage::new()),
+        ).unwrap();
+        let mut done = Item::new(ItemType::Todo, "done");
+        done.status = ItemStatus::Complete;
+        container.list_mut().items.push(done);
+        container.save().unwrap();
+        assert!(!container.path.exists());
+        assert!(matches!(container.archive(None), Err(ExitCode::UnsupportedStorage(_))));
+        assert_eq!(texts(&container.list().items), vec!["done"]);
+        container.backup_count = 1;
+        assert!(matches!(container.save(), Err(ExitCode::UnsupportedStorage(_))));
+        container.backup_count = 0;
+        container.mode = StorageMode::Journal;
+        assert!(matches!(container.save(), Err(ExitCode::UnsupportedStorage(_))));
+        let storage = MemoryStorage::with_list(container.list().clone());
+        assert!(matches!(
+            Container::create_with("memory-list", Box::new(storage)),
+            Err(ExitCode::ListExists(_)),
+        ));
+        let storage = MemoryStorage::with_list(container.list().clone());
+        let loaded = Container::load_with("memory-list", Box::new(storage)).unwrap();
+        assert_eq!(texts(&loaded.list().items), vec!["done"]);
+    }
 }
@@ -0,0 +1,54 @@
+use {
+    chrono::Duration,
+    crate::{ enums::Estimate, utils::format_duration, },
+    std::fmt::{ Display, Error as FormatError, Formatter, },
+};
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Effort {
+    pub points: f64,
+    pub minutes: u64,
+}
+impl Effort {
+    pub fn is_zero(&self) -> bool {
+        self.points.eq(&0_f64) && self.minutes.eq(&0)
+    }
+    pub fn add_estimate(&mut self, estimate: &Estimate) {
+        match estimate {
+            Estimate::Points(points) => self.points += points,
+            Estimate::Minutes(minutes) => self.minutes += *minutes as u64,
+        }
+    }
+    pub fn add(&mut self, other: &Effort) {
+        self.points += other.points;
+        self.minutes += other.minutes;
+    }
+}
+impl Display for Effort {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        let mut parts = Vec::new();
+        if self.points > 0_f64 {
+            parts.push(format!("{}pt", self.points));
+        }
+        if self.minutes > 0 {
+            parts.push(format_duration(&Duration::minutes(self.minutes as i64)));
+        }
+        if parts.is_empty() {
+            return fmt.write_str("0");
+        }
+        fmt.write_str(&parts.join(", "))
+    }
+}
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct EffortTotals {
+    pub remaining: Effort,
+    pub completed: Effort,
+}
+impl EffortTotals {
+    pub fn is_zero(&self) -> bool {
+        self.remaining.is_zero() && self.completed.is_zero()
+    }
+    pub fn add(&mut self, other: &EffortTotals) {
+        self.remaining.add(&other.remaining);
+        self.completed.add(&other.completed);
+    }
+}
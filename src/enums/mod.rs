@@ -1,10 +1,23 @@
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Datelike;
+use chrono::Local;
+use chrono::Months;
+use chrono::Weekday;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
+use serde::Serializer;
 use std::fmt::Display;
 use std::fmt::Error as FormatError;
 use std::fmt::Formatter;
 use std::str::FromStr;
+use std::path::Path;
 use std::path::PathBuf;
+use crate::json_style::JsonStyle;
+use crate::utils::format_duration;
+use crate::utils::get_printable_coords;
+#[derive(Debug)]
 pub enum ExitCode {
     Success,
     NoListName,
@@ -20,24 +33,48 @@ pub enum ExitCode {
     FailedToOpen(PathBuf),
     FailedToDeserialize(serde_json::Error),
     FailedToSerialize(serde_json::Error),
+    InvalidSearchPattern(regex::Error),
+    ItemNotFound(String),
+    DependencyCycle(String),
+    UnknownStatus(String),
+    ListExists(String),
+    ListNotFound,
+    #[cfg(feature = "sqlite")]
+    DatabaseError(rusqlite::Error),
+    FailedToSerializeAs(ListFormat, String),
+    FailedToDeserializeAs(ListFormat, String),
+    UnsupportedStorage(String),
+    NoScanMarkers,
 }
-impl Into<i32> for ExitCode {
-    fn into(self) -> i32 {
-        match self {
-            Self::Success => 0,
-            Self::NoEnvVar => 2,
-            Self::NoListName => 3,
-            Self::NoListItemMessage(_) => 4,
-            Self::NoListItemNumber(_) => 5,
-            Self::FileExists(_) => 6,
-            Self::FileDoesNotExist(_) => 7,
+impl From<ExitCode> for i32 {
+    fn from(val: ExitCode) -> Self {
+        match val {
+            ExitCode::Success => 0,
+            ExitCode::NoEnvVar => 2,
+            ExitCode::NoListName => 3,
+            ExitCode::NoListItemMessage(_) => 4,
+            ExitCode::NoListItemNumber(_) => 5,
+            ExitCode::FileExists(_) => 6,
+            ExitCode::FileDoesNotExist(_) => 7,
             //Self::PathFailed(_) => 8,
             //Self::FileCreationFailed(_) => 9,
-            Self::FailedToWrite(_) => 10,
-            Self::FailedToRead(_) => 11,
-            Self::FailedToOpen(_) => 12,
-            Self::FailedToDeserialize(_) => 13,
-            Self::FailedToSerialize(_) => 14,
+            ExitCode::FailedToWrite(_) => 10,
+            ExitCode::FailedToRead(_) => 11,
+            ExitCode::FailedToOpen(_) => 12,
+            ExitCode::FailedToDeserialize(_) => 13,
+            ExitCode::FailedToSerialize(_) => 14,
+            ExitCode::InvalidSearchPattern(_) => 15,
+            ExitCode::ItemNotFound(_) => 16,
+            ExitCode::DependencyCycle(_) => 17,
+            ExitCode::UnknownStatus(_) => 18,
+            ExitCode::ListExists(_) => 19,
+            ExitCode::ListNotFound => 20,
+            #[cfg(feature = "sqlite")]
+            ExitCode::DatabaseError(_) => 21,
+            ExitCode::FailedToSerializeAs(_, _) => 22,
+            ExitCode::FailedToDeserializeAs(_, _) => 23,
+            ExitCode::UnsupportedStorage(_) => 24,
+            ExitCode::NoScanMarkers => 25,
         }
     }
 }
@@ -48,16 +85,16 @@ impl Display for ExitCode {
             Self::NoListName => f.write_str("No list name for list"),
             Self::NoEnvVar => f.write_str("Environment variable TODO_LIST is not set"),
             Self::NoListItemMessage(s) => {
-                return f.write_str(&format!("No item-message for list \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("No item-message for list \"{}\"", s.to_str().unwrap()))
             },
             Self::NoListItemNumber(s) => {
-                return f.write_str(&format!("No item-number for list \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("No item-number for list \"{}\"", s.to_str().unwrap()))
             },
             Self::FileExists(s) => {
-                return f.write_str(&format!("File exists at path \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("File exists at path \"{}\"", s.to_str().unwrap()))
             },
             Self::FileDoesNotExist(s) => {
-                return f.write_str(&format!("File does not exist at path \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("File does not exist at path \"{}\"", s.to_str().unwrap()))
             },
             //Self::PathFailed(s) => {
             //    return f.write_str(&format!("Failed to derive path from \"{}\"", s.to_str().unwrap()));
@@ -66,19 +103,53 @@ impl Display for ExitCode {
             //    return f.write_str(&format!("Failed to create file at \"{}\"", s.to_str().unwrap()));
             //},
             Self::FailedToWrite(s) => {
-                return f.write_str(&format!("Failed to write to file \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("Failed to write to file \"{}\"", s.to_str().unwrap()))
             },
             Self::FailedToRead(s) => {
-                return f.write_str(&format!("Failed to read file \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("Failed to read file \"{}\"", s.to_str().unwrap()))
             },
             Self::FailedToOpen(s) => {
-                return f.write_str(&format!("Failed to open file \"{}\"", s.to_str().unwrap()));
+                f.write_str(&format!("Failed to open file \"{}\"", s.to_str().unwrap()))
             },
             Self::FailedToDeserialize(e) => {
-                return f.write_str(&format!("Failed to deserialize json: {}", e));
+                f.write_str(&format!("Failed to deserialize json: {}", e))
             },
             Self::FailedToSerialize(e) => {
-                return f.write_str(&format!("Failed to serialize to json: {}", e));
+                f.write_str(&format!("Failed to serialize to json: {}", e))
+            },
+            Self::InvalidSearchPattern(e) => {
+                f.write_str(&format!("Invalid search pattern: {}", e))
+            },
+            Self::ItemNotFound(s) => {
+                f.write_str(&format!("No item found for \"{}\"", s))
+            },
+            Self::DependencyCycle(s) => {
+                f.write_str(&format!("Dependency on \"{}\" would create a cycle", s))
+            },
+            Self::UnknownStatus(s) => {
+                f.write_str(&format!("Status \"{}\" is not defined for this list", s))
+            },
+            Self::ListExists(s) => {
+                f.write_str(&format!("List \"{}\" already exists in storage", s))
+            },
+            Self::ListNotFound => {
+                f.write_str("No list exists in storage")
+            },
+            #[cfg(feature = "sqlite")]
+            Self::DatabaseError(e) => {
+                f.write_str(&format!("Database error: {}", e))
+            },
+            Self::FailedToSerializeAs(format, e) => {
+                f.write_str(&format!("Failed to serialize list as {}: {}", format, e))
+            },
+            Self::FailedToDeserializeAs(format, e) => {
+                f.write_str(&format!("Failed to deserialize list as {}: {}", format, e))
+            },
+            Self::UnsupportedStorage(s) => {
+                f.write_str(&format!("{} requires a list stored in a file", s))
+            },
+            Self::NoScanMarkers => {
+                f.write_str("No markers given to scan for")
             },
         }
     }
@@ -88,6 +159,11 @@ pub enum PathExitCondition {
     NotExists,
     Ignore,
 }
+#[derive(Clone, PartialEq)]
+pub enum StorageMode {
+    Snapshot,
+    Journal,
+}
 #[derive(Clone)]
 pub enum PrintWhich {
     All,
@@ -122,16 +198,223 @@ impl FromStr for PrintWhich {
         }
     }
 }
+#[derive(Clone)]
+pub enum SearchMode {
+    Substring,
+    CaseInsensitive,
+    Regex,
+}
+impl Display for SearchMode {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Substring => fmt.write_str("substring"),
+            Self::CaseInsensitive => fmt.write_str("case-insensitive"),
+            Self::Regex => fmt.write_str("regex"),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseSearchModeError;
+impl Display for ParseSearchModeError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to SearchMode")
+    }
+}
+impl std::error::Error for ParseSearchModeError {}
+impl FromStr for SearchMode {
+    type Err = ParseSearchModeError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "substring" => Ok(Self::Substring),
+            "case-insensitive" => Ok(Self::CaseInsensitive),
+            "regex" => Ok(Self::Regex),
+            _ => Err(ParseSearchModeError {}),
+        }
+    }
+}
+#[derive(Clone, PartialEq)]
+pub enum SortKey {
+    Created,
+    Updated,
+    Status,
+    Text,
+    Priority,
+}
+impl Display for SortKey {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Created => fmt.write_str("created"),
+            Self::Updated => fmt.write_str("updated"),
+            Self::Status => fmt.write_str("status"),
+            Self::Text => fmt.write_str("text"),
+            Self::Priority => fmt.write_str("priority"),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseSortKeyError;
+impl Display for ParseSortKeyError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to SortKey")
+    }
+}
+impl std::error::Error for ParseSortKeyError {}
+impl FromStr for SortKey {
+    type Err = ParseSortKeyError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "created" => Ok(Self::Created),
+            "updated" => Ok(Self::Updated),
+            "status" => Ok(Self::Status),
+            "text" => Ok(Self::Text),
+            "priority" => Ok(Self::Priority),
+            _ => Err(ParseSortKeyError {}),
+        }
+    }
+}
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ListFormat {
+    Json(JsonStyle),
+    Toml,
+    Yaml,
+}
+impl ListFormat {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("toml") => Self::Toml,
+            Some("yaml") | Some("yml") => Self::Yaml,
+            _ => Self::Json(JsonStyle::default()),
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json(_) => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        }
+    }
+}
+impl Display for ListFormat {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str(self.extension())
+    }
+}
+#[derive(Debug)]
+pub struct ParseListFormatError;
+impl Display for ParseListFormatError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to ListFormat")
+    }
+}
+impl std::error::Error for ParseListFormatError {}
+impl FromStr for ListFormat {
+    type Err = ParseListFormatError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "json" => Ok(Self::Json(JsonStyle::compact())),
+            "pretty-json" => Ok(Self::Json(JsonStyle::pretty())),
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(ParseListFormatError {}),
+        }
+    }
+}
+#[derive(Clone, Copy, PartialEq)]
+pub enum TodoTxtNesting {
+    Flatten,
+    ParentKey,
+    Indent,
+}
+impl Display for TodoTxtNesting {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Flatten => fmt.write_str("flatten"),
+            Self::ParentKey => fmt.write_str("parent"),
+            Self::Indent => fmt.write_str("indent"),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseTodoTxtNestingError;
+impl Display for ParseTodoTxtNestingError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to TodoTxtNesting")
+    }
+}
+impl std::error::Error for ParseTodoTxtNestingError {}
+impl FromStr for TodoTxtNesting {
+    type Err = ParseTodoTxtNestingError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "flatten" => Ok(Self::Flatten),
+            "parent" => Ok(Self::ParentKey),
+            "indent" => Ok(Self::Indent),
+            _ => Err(ParseTodoTxtNestingError {}),
+        }
+    }
+}
+#[derive(Clone, Copy, PartialEq)]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+}
+impl TableFormat {
+    pub fn delimiter(&self) -> char {
+        match self {
+            Self::Csv => ',',
+            Self::Tsv => '\t',
+        }
+    }
+}
+impl Display for TableFormat {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Csv => fmt.write_str("csv"),
+            Self::Tsv => fmt.write_str("tsv"),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseTableFormatError;
+impl Display for ParseTableFormatError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to TableFormat")
+    }
+}
+impl std::error::Error for ParseTableFormatError {}
+impl FromStr for TableFormat {
+    type Err = ParseTableFormatError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err(ParseTableFormatError {}),
+        }
+    }
+}
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub enum ItemType {
     Todo,
     Note,
+    Heading,
+    Milestone,
+    Link,
+}
+impl ItemType {
+    pub fn is_actionable(&self) -> bool {
+        matches!(self, Self::Todo | Self::Milestone)
+    }
 }
 impl Display for ItemType {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
         match self {
             Self::Todo => fmt.write_str("todo"),
             Self::Note => fmt.write_str("note"),
+            Self::Heading => fmt.write_str("heading"),
+            Self::Milestone => fmt.write_str("milestone"),
+            Self::Link => fmt.write_str("link"),
         }
     }
 }
@@ -149,25 +432,386 @@ impl FromStr for ItemType {
         match input {
             "todo" => Ok(Self::Todo),
             "note" => Ok(Self::Note),
+            "heading" => Ok(Self::Heading),
+            "milestone" => Ok(Self::Milestone),
+            "link" => Ok(Self::Link),
             _ => Err(ParseItemTypeError {}),
         }
     }
 }
-#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[derive(PartialEq, Eq, Hash, Clone)]
 pub enum ItemStatus {
     Complete,
     Disabled,
     Incomplete,
+    InProgress,
+    Blocked,
+    Waiting,
+    Custom(String),
 }
 impl ItemStatus {
     pub fn symbol(&self) -> &str {
-        const COMPLETE: &'static str = "x";
-        const DISABLED: &'static str = "-";
-        const INCOMPLETE: &'static str = " ";
+        const COMPLETE: &str = "x";
+        const DISABLED: &str = "-";
+        const INCOMPLETE: &str = " ";
+        const IN_PROGRESS: &str = "~";
+        const BLOCKED: &str = "!";
+        const WAITING: &str = "?";
+        const CUSTOM: &str = "*";
         match self {
             Self::Complete => COMPLETE,
             Self::Disabled => DISABLED,
             Self::Incomplete => INCOMPLETE,
+            Self::InProgress => IN_PROGRESS,
+            Self::Blocked => BLOCKED,
+            Self::Waiting => WAITING,
+            Self::Custom(_) => CUSTOM,
+        }
+    }
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, Self::Custom(_))
+    }
+    pub fn default_cycle() -> Vec<Self> {
+        vec![ Self::Incomplete, Self::Complete, Self::Disabled, ]
+    }
+    pub fn next_in(&self, cycle: &[Self]) -> Self {
+        if cycle.is_empty() {
+            return self.next_in(&Self::default_cycle());
+        }
+        match cycle.iter().position(|status| status.eq(self)) {
+            Some(i) => cycle[(i + 1) % cycle.len()].clone(),
+            None => cycle[0].clone(),
+        }
+    }
+}
+impl Display for ItemStatus {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Complete => fmt.write_str("complete"),
+            Self::Disabled => fmt.write_str("disabled"),
+            Self::Incomplete => fmt.write_str("incomplete"),
+            Self::InProgress => fmt.write_str("in-progress"),
+            Self::Blocked => fmt.write_str("blocked"),
+            Self::Waiting => fmt.write_str("waiting"),
+            Self::Custom(name) => fmt.write_str(name),
+        }
+    }
+}
+impl Serialize for ItemStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Complete => serializer.serialize_str("Complete"),
+            Self::Disabled => serializer.serialize_str("Disabled"),
+            Self::Incomplete => serializer.serialize_str("Incomplete"),
+            Self::InProgress => serializer.serialize_str("InProgress"),
+            Self::Blocked => serializer.serialize_str("Blocked"),
+            Self::Waiting => serializer.serialize_str("Waiting"),
+            Self::Custom(name) => serializer.serialize_str(name),
+        }
+    }
+}
+impl<'de> Deserialize<'de> for ItemStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "Complete" => Ok(Self::Complete),
+            "Disabled" => Ok(Self::Disabled),
+            "Incomplete" => Ok(Self::Incomplete),
+            "InProgress" => Ok(Self::InProgress),
+            "Blocked" => Ok(Self::Blocked),
+            "Waiting" => Ok(Self::Waiting),
+            _ => Ok(Self::Custom(name)),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseItemStatusError;
+impl Display for ParseItemStatusError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to ItemStatus")
+    }
+}
+impl std::error::Error for ParseItemStatusError {}
+impl FromStr for ItemStatus {
+    type Err = ParseItemStatusError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match input {
+            "complete" => Ok(Self::Complete),
+            "disabled" => Ok(Self::Disabled),
+            "incomplete" => Ok(Self::Incomplete),
+            "in-progress" => Ok(Self::InProgress),
+            "blocked" => Ok(Self::Blocked),
+            "waiting" => Ok(Self::Waiting),
+            "" => Err(ParseItemStatusError {}),
+            _ if input.contains(char::is_whitespace) => Err(ParseItemStatusError {}),
+            _ => Ok(Self::Custom(input.to_string())),
+        }
+    }
+}
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub enum Recurrence {
+    Daily,
+    Weekly(Vec<Weekday>),
+    Monthly,
+    AfterCompletion(u32),
+}
+impl Recurrence {
+    fn step(&self, from: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Daily => from + Duration::days(1),
+            Self::Weekly(days) => {
+                if days.is_empty() {
+                    return from + Duration::days(7);
+                }
+                let mut next = from + Duration::days(1);
+                while !days.contains(&next.weekday()) {
+                    next += Duration::days(1);
+                }
+                next
+            },
+            Self::Monthly => {
+                match from.checked_add_months(Months::new(1)) {
+                    Some(next) => next,
+                    None => from + Duration::days(30),
+                }
+            },
+            Self::AfterCompletion(days) => from + Duration::days(*days as i64),
+        }
+    }
+    pub fn next_due(
+        &self, due: Option<DateTime<Local>>, now: DateTime<Local>,
+    ) -> DateTime<Local> {
+        if let Self::AfterCompletion(_) = self {
+            return self.step(now);
+        }
+        let mut next = self.step(due.unwrap_or(now));
+        while next <= now {
+            next = self.step(next);
+        }
+        next
+    }
+}
+impl Display for Recurrence {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Daily => fmt.write_str("daily"),
+            Self::Weekly(days) => {
+                if days.is_empty() {
+                    return fmt.write_str("weekly");
+                }
+                let days = days.iter().map(|day| {
+                    day.to_string().to_lowercase()
+                }).collect::<Vec<String>>().join(",");
+                fmt.write_str(&format!("weekly:{}", days))
+            },
+            Self::Monthly => fmt.write_str("monthly"),
+            Self::AfterCompletion(days) => fmt.write_str(&format!("every:{}", days)),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseRecurrenceError;
+impl Display for ParseRecurrenceError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to Recurrence")
+    }
+}
+impl std::error::Error for ParseRecurrenceError {}
+impl FromStr for Recurrence {
+    type Err = ParseRecurrenceError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let (kind, value) = match input.split_once(':') {
+            Some((kind, value)) => (kind, Some(value)),
+            None => (input, None),
+        };
+        match (kind, value) {
+            ("daily", None) => Ok(Self::Daily),
+            ("weekly", None) => Ok(Self::Weekly(Vec::new())),
+            ("weekly", Some(days)) => {
+                let mut weekdays = Vec::new();
+                for day in days.split(',') {
+                    match Weekday::from_str(day.trim()) {
+                        Ok(weekday) => weekdays.push(weekday),
+                        Err(_) => return Err(ParseRecurrenceError {}),
+                    }
+                }
+                Ok(Self::Weekly(weekdays))
+            },
+            ("monthly", None) => Ok(Self::Monthly),
+            ("every", Some(days)) => {
+                match days.parse::<u32>() {
+                    Ok(days) if days > 0 => Ok(Self::AfterCompletion(days)),
+                    _ => Err(ParseRecurrenceError {}),
+                }
+            },
+            _ => Err(ParseRecurrenceError {}),
+        }
+    }
+}
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub enum Estimate {
+    Points(f64),
+    Minutes(u32),
+}
+impl Display for Estimate {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Points(points) => fmt.write_str(&format!("{}pt", points)),
+            Self::Minutes(minutes) => {
+                fmt.write_str(&format_duration(&Duration::minutes(*minutes as i64)))
+            },
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseEstimateError;
+impl Display for ParseEstimateError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to Estimate")
+    }
+}
+impl std::error::Error for ParseEstimateError {}
+impl FromStr for Estimate {
+    type Err = ParseEstimateError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(ParseEstimateError {});
+        }
+        let points = input.strip_suffix("pts")
+            .or_else(|| input.strip_suffix("pt"))
+            .unwrap_or(&input);
+        if let Ok(points) = points.trim().parse::<f64>() {
+            if points.is_finite() && points >= 0_f64 {
+                return Ok(Self::Points(points));
+            }
+            return Err(ParseEstimateError {});
+        }
+        let mut minutes = 0_f64;
+        let mut number = String::new();
+        for c in input.chars() {
+            match c {
+                '0'..='9' | '.' => number.push(c),
+                'h' | 'm' => {
+                    let value = match number.parse::<f64>() {
+                        Ok(value) => value,
+                        Err(_) => return Err(ParseEstimateError {}),
+                    };
+                    number.clear();
+                    minutes += if c.eq(&'h') { value * 60_f64 } else { value };
+                },
+                ' ' => {},
+                _ => return Err(ParseEstimateError {}),
+            }
+        }
+        if !number.is_empty() || !minutes.is_finite() || minutes > u32::MAX as f64 {
+            return Err(ParseEstimateError {});
+        }
+        Ok(Self::Minutes(minutes.round() as u32))
+    }
+}
+#[derive(Clone, PartialEq)]
+pub enum ItemRef {
+    Id(String),
+    Path(Vec<usize>),
+}
+impl Display for ItemRef {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        match self {
+            Self::Id(id) => fmt.write_str(id),
+            Self::Path(path) => fmt.write_str(&get_printable_coords(path)),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseItemRefError;
+impl Display for ParseItemRefError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str("Failed to parse str to ItemRef")
+    }
+}
+impl std::error::Error for ParseItemRefError {}
+impl FromStr for ItemRef {
+    type Err = ParseItemRefError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(ParseItemRefError {});
+        }
+        let is_path = input.chars().all(|c| {
+            c.is_ascii_digit() || c.eq(&',') || c.is_whitespace()
+        });
+        if !is_path {
+            return Ok(Self::Id(input.to_string()));
+        }
+        let mut path = Vec::new();
+        for num in input.split(|c: char| c.eq(&',') || c.is_whitespace()) {
+            if num.is_empty() {
+                continue;
+            }
+            match num.parse::<usize>() {
+                Ok(num) if num > 0 => path.push(num),
+                _ => return Err(ParseItemRefError {}),
+            }
+        }
+        Ok(Self::Path(path))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        chrono::TimeZone,
+    };
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, 9, 0, 0).unwrap()
+    }
+    #[test]
+    fn recurrence_round_trips_through_strings() {
+        for input in ["daily", "weekly", "weekly:mon,fri", "monthly", "every:3"] {
+            assert_eq!(Recurrence::from_str(input).unwrap().to_string(), input);
+        }
+        for input in ["hourly", "weekly:funday", "every:0", "every", "daily:2"] {
+            assert!(Recurrence::from_str(input).is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn next_due_steps_from_the_previous_due_date() {
+        let now = at(2024, 3, 4);
+        let due = Recurrence::Daily.next_due(Some(at(2024, 3, 4)), now);
+        assert_eq!(due, at(2024, 3, 5));
+        let due = Recurrence::Monthly.next_due(Some(at(2024, 3, 10)), now);
+        assert_eq!(due, at(2024, 4, 10));
+    }
+    #[test]
+    fn next_due_skips_occurrences_already_in_the_past() {
+        let now = at(2024, 3, 20);
+        let due = Recurrence::Weekly(Vec::new()).next_due(Some(at(2024, 3, 1)), now);
+        assert_eq!(due, at(2024, 3, 22));
+        let weekdays = Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(weekdays.next_due(None, now), at(2024, 3, 21));
+    }
+    #[test]
+    fn after_completion_counts_from_now() {
+        let now = at(2024, 3, 20);
+        let due = Recurrence::AfterCompletion(3).next_due(Some(at(2024, 1, 1)), now);
+        assert_eq!(due, at(2024, 3, 23));
+    }
+    #[test]
+    fn estimates_parse_points_and_durations() {
+        let minutes = |input: &str| match Estimate::from_str(input) {
+            Ok(Estimate::Minutes(minutes)) => Some(minutes),
+            _ => None,
+        };
+        assert_eq!(minutes("1.5h"), Some(90));
+        assert_eq!(minutes("1h 30m"), Some(90));
+        assert_eq!(minutes("45m"), Some(45));
+        assert_eq!(minutes(".25h"), Some(15));
+        assert!(matches!(Estimate::from_str("3pts"), Ok(Estimate::Points(p)) if p == 3_f64));
+        assert!(matches!(Estimate::from_str("0.5"), Ok(Estimate::Points(p)) if p == 0.5));
+        for input in ["", "h", "1.2.3h", "2d", "-1h", "-3", "1h30", "NaN"] {
+            assert!(Estimate::from_str(input).is_err(), "{}", input);
         }
+        assert_eq!(Estimate::from_str("1.5h").unwrap().to_string(), "1h 30m");
     }
 }
@@ -0,0 +1,505 @@
+use {
+    chrono::NaiveDate,
+    crate::{
+        enums::{ ItemStatus, ItemType, PrintWhich, SearchMode, },
+        item::Item,
+        search::Search,
+        status_definition::StatusDefinition,
+    },
+    std::{
+        fmt::{ Display, Error as FormatError, Formatter, },
+        str::FromStr,
+    },
+};
+#[derive(Clone, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+impl Comparison {
+    fn compare<T: PartialOrd>(&self, lhs: &T, rhs: &T) -> bool {
+        match self {
+            Self::Less => lhs < rhs,
+            Self::LessOrEqual => lhs <= rhs,
+            Self::Equal => lhs == rhs,
+            Self::GreaterOrEqual => lhs >= rhs,
+            Self::Greater => lhs > rhs,
+        }
+    }
+}
+#[derive(Clone, Default)]
+pub struct FilterContext {
+    pub statuses: Vec<StatusDefinition>,
+    pub user: Option<String>,
+}
+impl FilterContext {
+    pub fn new(statuses: Vec<StatusDefinition>, user: Option<String>) -> Self {
+        Self { statuses, user, }
+    }
+    pub fn definition(&self, status: &ItemStatus) -> Option<&StatusDefinition> {
+        let name = status.to_string();
+        self.statuses.iter().find(|definition| definition.name.eq(&name))
+    }
+    pub fn is_done(&self, status: &ItemStatus) -> bool {
+        match self.definition(status) {
+            Some(definition) => definition.done,
+            None => status.eq(&ItemStatus::Complete),
+        }
+    }
+    pub fn is_open(&self, status: &ItemStatus) -> bool {
+        !self.is_done(status) && status.ne(&ItemStatus::Disabled)
+    }
+    pub fn symbol(&self, status: &ItemStatus) -> String {
+        match self.definition(status) {
+            Some(definition) => definition.symbol.clone(),
+            None => status.symbol().to_string(),
+        }
+    }
+}
+#[derive(Clone)]
+pub enum Filter {
+    All,
+    Status(ItemStatus),
+    Open,
+    Done,
+    Type(ItemType),
+    Tag(String),
+    Priority(Comparison, u8),
+    Hidden,
+    Text(Search),
+    Created(Comparison, NaiveDate),
+    Updated(Comparison, NaiveDate),
+    Due(Comparison, NaiveDate),
+    Field(String, Option<String>),
+    Assignee(String),
+    Unassigned,
+    Mine,
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+impl Filter {
+    pub fn matches(&self, item: &Item) -> bool {
+        self.matches_in(item, &FilterContext::default())
+    }
+    pub fn matches_in(&self, item: &Item, context: &FilterContext) -> bool {
+        match self {
+            Self::All => true,
+            Self::Status(status) => item.status.eq(status),
+            Self::Open => context.is_open(&item.status),
+            Self::Done => context.is_done(&item.status),
+            Self::Type(item_type) => item.item_type.eq(item_type),
+            Self::Tag(tag) => item.tags.contains(tag),
+            Self::Priority(cmp, priority) => {
+                match item.priority {
+                    Some(item_priority) => cmp.compare(&item_priority, priority),
+                    None => false,
+                }
+            },
+            Self::Hidden => item.hidden,
+            Self::Text(search) => search.is_match(&item.text),
+            Self::Created(cmp, date) => {
+                cmp.compare(&item.created.date_naive(), date)
+            },
+            Self::Updated(cmp, date) => {
+                cmp.compare(&item.last_updated.date_naive(), date)
+            },
+            Self::Due(cmp, date) => {
+                match item.due {
+                    Some(due) => cmp.compare(&due.date_naive(), date),
+                    None => false,
+                }
+            },
+            Self::Field(key, value) => {
+                match (item.field_text(key), value) {
+                    (Some(_), None) => true,
+                    (Some(item_value), Some(value)) => item_value.eq(value),
+                    (None, _) => false,
+                }
+            },
+            Self::Assignee(assignee) => item.assignees.contains(assignee),
+            Self::Unassigned => item.assignees.is_empty(),
+            Self::Mine => {
+                match &context.user {
+                    Some(user) => item.assignees.contains(user),
+                    None => false,
+                }
+            },
+            Self::And(lhs, rhs) => {
+                lhs.matches_in(item, context) && rhs.matches_in(item, context)
+            },
+            Self::Or(lhs, rhs) => {
+                lhs.matches_in(item, context) || rhs.matches_in(item, context)
+            },
+            Self::Not(filter) => !filter.matches_in(item, context),
+        }
+    }
+    pub fn matches_tree(&self, item: &Item, context: &FilterContext) -> bool {
+        if self.matches_in(item, context) {
+            return true;
+        }
+        item.sub_items.iter().any(|sub| self.matches_tree(sub, context))
+    }
+}
+impl Filter {
+    fn actionable() -> Self {
+        Self::Or(
+            Box::new(Self::Type(ItemType::Todo)),
+            Box::new(Self::Type(ItemType::Milestone)),
+        )
+    }
+}
+impl From<&PrintWhich> for Filter {
+    fn from(print_which: &PrintWhich) -> Self {
+        match print_which {
+            PrintWhich::All => Self::All,
+            PrintWhich::Complete => Self::And(
+                Box::new(Self::actionable()),
+                Box::new(Self::Done),
+            ),
+            PrintWhich::Incomplete => Self::And(
+                Box::new(Self::actionable()),
+                Box::new(Self::Open),
+            ),
+        }
+    }
+}
+#[derive(Debug)]
+pub struct ParseFilterError(String);
+impl Display for ParseFilterError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FormatError> {
+        fmt.write_str(&format!("Failed to parse filter: {}", self.0))
+    }
+}
+impl std::error::Error for ParseFilterError {}
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseFilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.peek().copied() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c.eq(&'(') {
+            chars.next();
+            tokens.push(Token::Open);
+            continue;
+        }
+        if c.eq(&')') {
+            chars.next();
+            tokens.push(Token::Close);
+            continue;
+        }
+        let mut word = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.peek().copied() {
+            if !quoted && (c.is_whitespace() || c.eq(&'(') || c.eq(&')')) {
+                break;
+            }
+            chars.next();
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => {
+                    match chars.next() {
+                        Some(escaped) => word.push(escaped),
+                        None => word.push(c),
+                    }
+                },
+                _ => word.push(c),
+            }
+        }
+        if quoted {
+            return Err(ParseFilterError(format!("Unterminated quote in \"{}\"", word)));
+        }
+        tokens.push(Token::Word(word));
+    }
+    Ok(tokens)
+}
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    context: &'a FilterContext,
+}
+impl Parser<'_> {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.tokens.get(self.position) {
+            Some(Token::Word(word)) => word.to_lowercase().eq(keyword),
+            _ => false,
+        }
+    }
+    fn parse_or(&mut self) -> Result<Filter, ParseFilterError> {
+        let mut filter = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.position += 1;
+            let rhs = self.parse_and()?;
+            filter = Filter::Or(Box::new(filter), Box::new(rhs));
+        }
+        Ok(filter)
+    }
+    fn parse_and(&mut self) -> Result<Filter, ParseFilterError> {
+        let mut filter = self.parse_unary()?;
+        loop {
+            match self.tokens.get(self.position) {
+                None | Some(Token::Close) => break,
+                _ if self.peek_keyword("or") => break,
+                _ if self.peek_keyword("and") => self.position += 1,
+                _ => {},
+            }
+            let rhs = self.parse_unary()?;
+            filter = Filter::And(Box::new(filter), Box::new(rhs));
+        }
+        Ok(filter)
+    }
+    fn parse_unary(&mut self) -> Result<Filter, ParseFilterError> {
+        if self.peek_keyword("not") {
+            self.position += 1;
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+    fn parse_primary(&mut self) -> Result<Filter, ParseFilterError> {
+        let position = self.position;
+        self.position += 1;
+        match self.tokens.get(position) {
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(filter)
+                    },
+                    _ => Err(ParseFilterError("Expected \")\"".to_string())),
+                }
+            },
+            Some(Token::Word(word)) => parse_term(word, self.context),
+            Some(Token::Close) => {
+                Err(ParseFilterError("Unexpected \")\"".to_string()))
+            },
+            None => {
+                Err(ParseFilterError("Unexpected end of filter".to_string()))
+            },
+        }
+    }
+}
+fn parse_date(value: &str) -> Result<NaiveDate, ParseFilterError> {
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => Ok(date),
+        Err(_) => Err(ParseFilterError(format!("Invalid date \"{}\"", value))),
+    }
+}
+fn parse_comparison(op: &str) -> Option<Comparison> {
+    match op {
+        "<" => Some(Comparison::Less),
+        "<=" => Some(Comparison::LessOrEqual),
+        ":" | "=" => Some(Comparison::Equal),
+        ">=" => Some(Comparison::GreaterOrEqual),
+        ">" => Some(Comparison::Greater),
+        _ => None,
+    }
+}
+fn parse_term(word: &str, context: &FilterContext) -> Result<Filter, ParseFilterError> {
+    match word.to_lowercase().as_str() {
+        "all" => return Ok(Filter::All),
+        "open" => return Ok(Filter::Open),
+        "done" => return Ok(Filter::Done),
+        "hidden" => return Ok(Filter::Hidden),
+        "mine" => return Ok(Filter::Mine),
+        "unassigned" => return Ok(Filter::Unassigned),
+        _ => {},
+    }
+    let op_start = match word.find(|c| ":~<>=".contains(c)) {
+        Some(i) => i,
+        None => return Err(ParseFilterError(format!("Unknown term \"{}\"", word))),
+    };
+    let key = word[..op_start].to_lowercase();
+    if let Some(field) = word[..op_start].strip_prefix("field.") {
+        let value = &word[op_start + 1..];
+        return match &word[op_start..op_start + 1] {
+            ":" | "=" if !field.is_empty() => {
+                Ok(Filter::Field(field.to_string(), Some(value.to_string())))
+            },
+            _ => Err(ParseFilterError(format!("Invalid term \"{}\"", word))),
+        };
+    }
+    let rest = &word[op_start..];
+    let (op, value) = if rest.starts_with(">=") || rest.starts_with("<=") {
+        rest.split_at(2)
+    } else {
+        rest.split_at(1)
+    };
+    let invalid = || ParseFilterError(format!("Invalid term \"{}\"", word));
+    match (key.as_str(), op) {
+        ("status", ":") | ("status", "=") => {
+            match ItemStatus::from_str(value) {
+                Ok(status) if status.is_builtin() || context.definition(&status).is_some() => {
+                    Ok(Filter::Status(status))
+                },
+                Ok(_) => Err(ParseFilterError(format!("Status \"{}\" is not defined", value))),
+                Err(_) => Err(invalid()),
+            }
+        },
+        ("type", ":") | ("type", "=") => {
+            match ItemType::from_str(value) {
+                Ok(item_type) => Ok(Filter::Type(item_type)),
+                Err(_) => Err(invalid()),
+            }
+        },
+        ("tag", ":") | ("tag", "=") => Ok(Filter::Tag(value.to_string())),
+        ("has", ":") | ("has", "=") => Ok(Filter::Field(value.to_string(), None)),
+        ("assignee", ":") | ("assignee", "=") => {
+            Ok(Filter::Assignee(value.trim_start_matches('@').to_string()))
+        },
+        ("hidden", ":") | ("hidden", "=") => {
+            match value {
+                "true" => Ok(Filter::Hidden),
+                "false" => Ok(Filter::Not(Box::new(Filter::Hidden))),
+                _ => Err(invalid()),
+            }
+        },
+        ("text", ":") | ("text", "=") => {
+            match Search::new(value, &SearchMode::CaseInsensitive) {
+                Ok(search) => Ok(Filter::Text(search)),
+                Err(_) => Err(invalid()),
+            }
+        },
+        ("text", "~") => {
+            match Search::new(value, &SearchMode::Regex) {
+                Ok(search) => Ok(Filter::Text(search)),
+                Err(e) => Err(ParseFilterError(format!("{}", e))),
+            }
+        },
+        ("priority", _) => {
+            let cmp = parse_comparison(op).ok_or_else(invalid)?;
+            match value.parse::<u8>() {
+                Ok(priority) => Ok(Filter::Priority(cmp, priority)),
+                Err(_) => Err(invalid()),
+            }
+        },
+        ("created", _) | ("updated", _) | ("due", _) => {
+            let cmp = parse_comparison(op).ok_or_else(invalid)?;
+            let date = parse_date(value)?;
+            match key.as_str() {
+                "created" => Ok(Filter::Created(cmp, date)),
+                "updated" => Ok(Filter::Updated(cmp, date)),
+                _ => Ok(Filter::Due(cmp, date)),
+            }
+        },
+        _ => Err(invalid()),
+    }
+}
+impl Filter {
+    pub fn parse_in(input: &str, context: &FilterContext) -> Result<Self, ParseFilterError> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Ok(Self::All);
+        }
+        let mut parser = Parser { tokens, position: 0, context, };
+        let filter = parser.parse_or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(ParseFilterError("Unexpected \")\"".to_string()));
+        }
+        Ok(filter)
+    }
+}
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+    fn from_str(input: &str) -> Result<Self, <Self as FromStr>::Err> {
+        Self::parse_in(input, &FilterContext::default())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_utils::todo,
+    };
+    fn parse(input: &str) -> Filter {
+        Filter::from_str(input).unwrap()
+    }
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert!(parse("  ").matches(&Item::new(ItemType::Note, "note")));
+    }
+    #[test]
+    fn terms_are_joined_by_implicit_and() {
+        let mut item = todo("write docs", ItemStatus::Incomplete);
+        item.tags.push("work".to_string());
+        assert!(parse("open tag:work").matches(&item));
+        assert!(parse("open and tag:work").matches(&item));
+        assert!(!parse("done tag:work").matches(&item));
+    }
+    #[test]
+    fn or_binds_looser_than_and() {
+        let item = todo("write docs", ItemStatus::Complete);
+        assert!(parse("done or tag:home open").matches(&item));
+        assert!(!parse("(done or tag:home) open").matches(&item));
+    }
+    #[test]
+    fn not_and_hidden_terms() {
+        let mut item = todo("secret", ItemStatus::Incomplete);
+        assert!(parse("not hidden").matches(&item));
+        assert!(parse("hidden:false").matches(&item));
+        item.hidden = true;
+        assert!(parse("hidden:true").matches(&item));
+        assert!(!parse("not hidden").matches(&item));
+    }
+    #[test]
+    fn text_terms_support_quotes_and_regex() {
+        let item = todo("Call the Bank", ItemStatus::Incomplete);
+        assert!(parse("text:\"the bank\"").matches(&item));
+        assert!(parse("text~^Call").matches(&item));
+        assert!(!parse("text~^bank").matches(&item));
+    }
+    #[test]
+    fn comparisons_on_priority_and_dates() {
+        let mut item = todo("pay rent", ItemStatus::Incomplete);
+        item.priority = Some(2);
+        assert!(parse("priority<=2").matches(&item));
+        assert!(!parse("priority>2").matches(&item));
+        let today = item.created.date_naive().format("%Y-%m-%d").to_string();
+        assert!(parse(&format!("created:{}", today)).matches(&item));
+        assert!(!parse(&format!("created<{}", today)).matches(&item));
+        assert!(!parse("due<2100-01-01").matches(&item));
+    }
+    #[test]
+    fn custom_statuses_follow_the_context() {
+        let item = todo("ship it", ItemStatus::Custom("shipped".to_string()));
+        let context = FilterContext::new(
+            vec![StatusDefinition::new("shipped", "s", None, true)], None,
+        );
+        let filter = Filter::parse_in("status:shipped", &context).unwrap();
+        assert!(filter.matches_in(&item, &context));
+        assert!(Filter::parse_in("status:in-progress", &context).is_ok());
+        assert!(Filter::parse_in("status:shiped", &context).is_err());
+        assert!("status:shipped".parse::<Filter>().is_err());
+        assert!(parse("done").matches_in(&item, &context));
+        assert!(!parse("done").matches(&item));
+    }
+    #[test]
+    fn print_which_converts_to_filters() {
+        let done = todo("a", ItemStatus::Complete);
+        let open = todo("b", ItemStatus::InProgress);
+        let note = Item::new(ItemType::Note, "c");
+        let complete = Filter::from(&PrintWhich::Complete);
+        let incomplete = Filter::from(&PrintWhich::Incomplete);
+        assert!(complete.matches(&done) && !complete.matches(&open));
+        assert!(incomplete.matches(&open) && !incomplete.matches(&done));
+        assert!(!complete.matches(&note) && !incomplete.matches(&note));
+        assert!(Filter::from(&PrintWhich::All).matches(&note));
+    }
+    #[test]
+    fn invalid_filters_are_rejected() {
+        for input in ["(open", "open)", "\"open", "bogus", "priority:high",
+            "created:yesterday", "type:task", "text~(", "field.:x"] {
+            assert!(Filter::from_str(input).is_err(), "{}", input);
+        }
+    }
+}
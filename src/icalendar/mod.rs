@@ -0,0 +1,482 @@
+use {
+    chrono::{
+        DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+        Weekday,
+    },
+    crate::{
+        enums::{ ItemStatus, ItemType, },
+        item::Item,
+        list::List,
+        utils::{ build_tree, date_to_local, },
+    },
+    std::{ collections::HashMap, str::FromStr, },
+};
+const LINE_LIMIT: usize = 75;
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+fn unescape(value: &str) -> String {
+    let mut output = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') | Some('N') => output.push('\n'),
+                Some(next) => output.push(next),
+                None => output.push('\\'),
+            },
+            _ => output.push(c),
+        }
+    }
+    output
+}
+fn split_escaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            _ if escaped => {
+                current.push('\\');
+                current.push(c);
+                escaped = false;
+            },
+            '\\' => escaped = true,
+            _ if c == separator => parts.push(unescape(&std::mem::take(&mut current))),
+            _ => current.push(c),
+        }
+    }
+    parts.push(unescape(&current));
+    parts
+}
+fn fold(line: &str, output: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            output.push_str("\r\n ");
+            width = 1;
+        }
+        output.push(c);
+        width += c.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+fn format_time(time: &DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+struct Observance {
+    start: NaiveDateTime,
+    offset: i64,
+    rule: Option<(u32, i64, Weekday)>,
+}
+impl Observance {
+    fn onset(&self, year: i32) -> Option<NaiveDateTime> {
+        let (month, nth, weekday) = match self.rule {
+            Some(rule) => rule,
+            None => return Some(self.start).filter(|start| start.year() <= year),
+        };
+        if self.start.year() > year {
+            return None;
+        }
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let last = NaiveDate::from_ymd_opt(year + month as i32 / 12, month % 12 + 1, 1)?
+            .pred_opt()?;
+        let date = match nth > 0 {
+            true => {
+                let shift = (7 + weekday.num_days_from_monday() as i64
+                    - first.weekday().num_days_from_monday() as i64) % 7;
+                first + Duration::days(shift + (nth - 1) * 7)
+            },
+            false => {
+                let shift = (7 + last.weekday().num_days_from_monday() as i64
+                    - weekday.num_days_from_monday() as i64) % 7;
+                last - Duration::days(shift + (-nth - 1) * 7)
+            },
+        };
+        Some(date.and_time(self.start.time()))
+    }
+}
+type Zones = HashMap<String, Vec<Observance>>;
+fn parse_offset(value: &str) -> Option<i64> {
+    let digits = value.get(1..)?;
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours = digits.get(0..2)?.parse::<i64>().ok()?;
+    let minutes = digits.get(2..4)?.parse::<i64>().ok()?;
+    let seconds = digits.get(4..6).and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+fn parse_rule(value: &str) -> Option<(u32, i64, Weekday)> {
+    let parts = value.split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect::<HashMap<&str, &str>>();
+    if parts.get("FREQ").ne(&Some(&"YEARLY")) {
+        return None;
+    }
+    let month = parts.get("BYMONTH")?.parse::<u32>().ok()?;
+    let by_day = parts.get("BYDAY")?;
+    let split = by_day.len().checked_sub(2)?;
+    let weekday = match &by_day[split..] {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let nth = match &by_day[..split] {
+        "" | "+" => 1,
+        nth => nth.trim_start_matches('+').parse::<i64>().ok().filter(|nth| *nth != 0)?,
+    };
+    Some((month, nth, weekday))
+}
+fn import_observance(properties: &[Property]) -> Option<Observance> {
+    let mut start = None;
+    let mut offset = None;
+    let mut rule = None;
+    for property in properties.iter() {
+        match property.name.as_str() {
+            "DTSTART" => {
+                start = NaiveDateTime::parse_from_str(&property.value, "%Y%m%dT%H%M%S").ok();
+            },
+            "TZOFFSETTO" => offset = parse_offset(&property.value),
+            "RRULE" => rule = parse_rule(&property.value),
+            _ => {},
+        }
+    }
+    Some(Observance { start: start?, offset: offset?, rule, })
+}
+fn zone_offset(observances: &[Observance], time: &NaiveDateTime) -> Option<i64> {
+    let year = time.year();
+    observances.iter()
+        .flat_map(|observance| {
+            [year - 1, year].into_iter()
+                .filter_map(|year| observance.onset(year))
+                .filter(|onset| onset <= time)
+                .map(|onset| (onset, observance.offset))
+        })
+        .max_by_key(|(onset, _)| *onset)
+        .map(|(_, offset)| offset)
+}
+fn parse_time(value: &str, tzid: Option<&String>, zones: &Zones) -> Option<DateTime<Local>> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&time).with_timezone(&Local));
+    }
+    let time = match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(time) => time,
+        Err(_) => return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(date_to_local),
+    };
+    let offset = match tzid.map(|tzid| tzid.as_str()) {
+        Some("UTC") | Some("Etc/UTC") | Some("GMT") | Some("Etc/GMT") => Some(0),
+        Some(tzid) => zones.get(tzid).and_then(|observances| zone_offset(observances, &time)),
+        None => None,
+    };
+    match offset {
+        Some(offset) => {
+            let utc = time - Duration::seconds(offset);
+            Some(Utc.from_utc_datetime(&utc).with_timezone(&Local))
+        },
+        None => Local.from_local_datetime(&time).earliest(),
+    }
+}
+fn status_value(status: &ItemStatus) -> &'static str {
+    match status {
+        ItemStatus::Complete => "COMPLETED",
+        ItemStatus::Disabled => "CANCELLED",
+        ItemStatus::InProgress => "IN-PROCESS",
+        _ => "NEEDS-ACTION",
+    }
+}
+fn export_item(item: &Item, parent: Option<&Item>, stamp: &str, output: &mut String) {
+    fold("BEGIN:VTODO", output);
+    fold(&format!("UID:{}", item.id), output);
+    fold(&format!("DTSTAMP:{}", stamp), output);
+    fold(&format!("SUMMARY:{}", escape(&item.text)), output);
+    fold(&format!("STATUS:{}", status_value(&item.status)), output);
+    fold(&format!("CREATED:{}", format_time(&item.created)), output);
+    fold(&format!("LAST-MODIFIED:{}", format_time(&item.last_updated)), output);
+    if item.status.eq(&ItemStatus::Complete) {
+        fold(&format!("COMPLETED:{}", format_time(&item.last_updated)), output);
+    }
+    if let Some(parent) = parent {
+        fold(&format!("RELATED-TO;RELTYPE=PARENT:{}", parent.id), output);
+    }
+    for dependency in item.depends_on.iter() {
+        fold(&format!("RELATED-TO;RELTYPE=DEPENDS-ON:{}", dependency), output);
+    }
+    if let Some(due) = item.due {
+        fold(&format!("DUE:{}", format_time(&due)), output);
+    }
+    if let Some(priority) = item.priority {
+        fold(&format!("PRIORITY:{}", priority.clamp(1, 9)), output);
+    }
+    if !item.tags.is_empty() {
+        let tags = item.tags.iter()
+            .map(|tag| escape(tag))
+            .collect::<Vec<String>>();
+        fold(&format!("CATEGORIES:{}", tags.join(",")), output);
+    }
+    if let Some(link) = &item.link {
+        fold(&format!("URL:{}", link), output);
+    }
+    if item.item_type.ne(&ItemType::Todo) {
+        fold(&format!("X-TODO-TYPE:{}", item.item_type), output);
+    }
+    match &item.status {
+        ItemStatus::Blocked | ItemStatus::Waiting | ItemStatus::Custom(_) => {
+            fold(&format!("X-TODO-STATUS:{}", escape(&item.status.to_string())), output);
+        },
+        _ => {},
+    }
+    if item.hidden {
+        fold("X-TODO-HIDDEN:TRUE", output);
+    }
+    fold("END:VTODO", output);
+    for sub in item.sub_items.iter() {
+        export_item(sub, Some(item), stamp, output);
+    }
+}
+pub fn export(list: &List) -> String {
+    let stamp = format_time(&Local::now());
+    let mut output = String::new();
+    fold("BEGIN:VCALENDAR", &mut output);
+    fold("VERSION:2.0", &mut output);
+    fold("PRODID:-//todo-rs//todo_core//EN", &mut output);
+    fold(&format!("X-WR-CALNAME:{}", escape(&list.name)), &mut output);
+    for item in list.items.iter() {
+        export_item(item, None, &stamp, &mut output);
+    }
+    fold("END:VCALENDAR", &mut output);
+    output
+}
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let split = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            quoted = !quoted;
+        }
+        *c == ':' && !quoted
+    })?.0;
+    let mut parts = line[..split].split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: line[split + 1..].to_string() })
+}
+fn import_item(properties: &[Property], zones: &Zones) -> (Item, Option<String>) {
+    let mut item = Item::new(ItemType::Todo, "");
+    let mut parent = None;
+    let mut extra_status = None;
+    for property in properties.iter() {
+        let value = property.value.as_str();
+        let time = |value| parse_time(value, property.params.get("TZID"), zones);
+        match property.name.as_str() {
+            "UID" => item.id = value.to_string(),
+            "SUMMARY" => item.text = unescape(value),
+            "STATUS" => {
+                item.status = match value.to_uppercase().as_str() {
+                    "COMPLETED" => ItemStatus::Complete,
+                    "CANCELLED" => ItemStatus::Disabled,
+                    "IN-PROCESS" => ItemStatus::InProgress,
+                    _ => ItemStatus::Incomplete,
+                };
+            },
+            "CREATED" => item.created = time(value).unwrap_or(item.created),
+            "LAST-MODIFIED" => item.last_updated = time(value).unwrap_or(item.last_updated),
+            "RELATED-TO" => {
+                let reltype = property.params.get("RELTYPE")
+                    .map(|reltype| reltype.to_uppercase());
+                match reltype.as_deref() {
+                    None | Some("PARENT") => parent = Some(value.to_string()),
+                    Some("DEPENDS-ON") => item.depends_on.push(value.to_string()),
+                    _ => {},
+                }
+            },
+            "DUE" => item.due = time(value),
+            "PRIORITY" => item.priority = value.parse().ok().filter(|priority| *priority > 0),
+            "CATEGORIES" => {
+                item.tags.extend(split_escaped(value, ',')
+                    .into_iter()
+                    .filter(|tag| !tag.is_empty()));
+            },
+            "URL" => item.link = Some(value.to_string()),
+            "X-TODO-TYPE" => {
+                if let Ok(item_type) = ItemType::from_str(value) {
+                    item.item_type = item_type;
+                }
+            },
+            "X-TODO-STATUS" => extra_status = ItemStatus::from_str(&unescape(value)).ok(),
+            "X-TODO-HIDDEN" => item.hidden = value.eq_ignore_ascii_case("TRUE"),
+            _ => {},
+        }
+    }
+    if let Some(status) = extra_status {
+        item.status = status;
+    }
+    (item, parent)
+}
+pub fn import(name: impl AsRef<str>, content: &str) -> List {
+    let mut components: Vec<(String, Vec<Property>)> = Vec::new();
+    let mut todo_properties = Vec::new();
+    let mut zones: Zones = HashMap::new();
+    for property in unfold(content).iter().filter_map(|line| parse_property(line)) {
+        match property.name.as_str() {
+            "BEGIN" => components.push((property.value.to_uppercase(), Vec::new())),
+            "END" => {
+                let component = property.value.to_uppercase();
+                let (name, properties) = match components.pop() {
+                    Some(open) if open.0.eq(&component) => open,
+                    Some(open) => {
+                        components.push(open);
+                        continue;
+                    },
+                    None => continue,
+                };
+                match (name.as_str(), components.last_mut()) {
+                    ("VTODO", _) => todo_properties.push(properties),
+                    ("STANDARD" | "DAYLIGHT", Some((zone, zone_properties)))
+                        if zone.as_str().eq("VTIMEZONE") =>
+                    {
+                        let tzid = zone_properties.iter()
+                            .find(|property| property.name.eq("TZID"))
+                            .map(|property| property.value.clone());
+                        if let (Some(tzid), Some(observance)) =
+                            (tzid, import_observance(&properties))
+                        {
+                            zones.entry(tzid).or_default().push(observance);
+                        }
+                    },
+                    _ => {},
+                }
+            },
+            _ => {
+                if let Some((_, properties)) = components.last_mut() {
+                    properties.push(property);
+                }
+            },
+        }
+    }
+    let todos = todo_properties.iter()
+        .map(|properties| import_item(properties, &zones))
+        .collect::<Vec<(Item, Option<String>)>>();
+    let by_id = todos.iter().enumerate()
+        .map(|(i, (item, _))| (item.id.clone(), i))
+        .collect::<HashMap<String, usize>>();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, (_, parent)) in todos.iter().enumerate() {
+        match parent.as_ref().and_then(|parent| by_id.get(parent)) {
+            Some(parent) if parent.ne(&i) => children.entry(*parent).or_default().push(i),
+            _ => roots.push(i),
+        }
+    }
+    let mut items = todos.into_iter()
+        .map(|(item, _)| Some(item))
+        .collect::<Vec<Option<Item>>>();
+    let mut list = List::new(name.as_ref().to_string());
+    for i in roots.into_iter().chain(0..items.len()) {
+        if let Some(item) = build_tree(i, &mut items, &children) {
+            list.items.push(item);
+        }
+    }
+    list.ensure_ids();
+    list.define_missing_statuses();
+    list
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn utc(time: &Option<DateTime<Local>>) -> String {
+        time.unwrap().with_timezone(&Utc).format("%Y-%m-%d %H:%M").to_string()
+    }
+    #[test]
+    fn alarm_properties_stay_out_of_the_todo() {
+        let content = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:rent\r\nSUMMARY:Pay rent\r\n\
+            BEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:Alarm\r\nUID:alarm\r\n\
+            STATUS:COMPLETED\r\nEND:VALARM\r\nPRIORITY:3\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let list = import("calendar", content);
+        assert_eq!(list.items.len(), 1);
+        assert_eq!(list.items[0].id, "rent");
+        assert_eq!(list.items[0].text, "Pay rent");
+        assert!(list.items[0].status.eq(&ItemStatus::Incomplete));
+        assert_eq!(list.items[0].priority, Some(3));
+    }
+    #[test]
+    fn due_times_follow_their_tzid() {
+        let content = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VTODO\r\nUID:summer\r\nSUMMARY:Summer\r\n\
+            DUE;TZID=America/New_York:20240715T090000\r\nEND:VTODO\r\n\
+            BEGIN:VTODO\r\nUID:winter\r\nSUMMARY:Winter\r\n\
+            DUE;TZID=\"America/New_York\":20240115T090000\r\nEND:VTODO\r\n\
+            BEGIN:VTODO\r\nUID:utc\r\nSUMMARY:Utc\r\nDUE;TZID=UTC:20240115T090000\r\n\
+            END:VTODO\r\n\
+            BEGIN:VTIMEZONE\r\nTZID:America/New_York\r\n\
+            BEGIN:DAYLIGHT\r\nDTSTART:20070311T020000\r\nTZOFFSETFROM:-0500\r\n\
+            TZOFFSETTO:-0400\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\nEND:DAYLIGHT\r\n\
+            BEGIN:STANDARD\r\nDTSTART:20071104T020000\r\nTZOFFSETFROM:-0400\r\n\
+            TZOFFSETTO:-0500\r\nRRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\nEND:STANDARD\r\n\
+            END:VTIMEZONE\r\nEND:VCALENDAR\r\n";
+        let list = import("calendar", content);
+        assert_eq!(utc(&list.items[0].due), "2024-07-15 13:00");
+        assert_eq!(utc(&list.items[1].due), "2024-01-15 14:00");
+        assert_eq!(utc(&list.items[2].due), "2024-01-15 09:00");
+    }
+    #[test]
+    fn last_weekday_rules_pick_the_final_week() {
+        let observance = Observance {
+            start: NaiveDate::from_ymd_opt(1996, 10, 27).unwrap().and_hms_opt(3, 0, 0).unwrap(),
+            offset: 3600,
+            rule: parse_rule("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU"),
+        };
+        let onset = observance.onset(2024).unwrap();
+        assert_eq!(onset.date(), NaiveDate::from_ymd_opt(2024, 10, 27).unwrap());
+        assert_eq!(parse_offset("+0530"), Some(19800));
+    }
+    #[test]
+    fn export_round_trips_the_tree() {
+        let mut list = List::new("calendar".to_string());
+        let mut parent = Item::new(ItemType::Todo, "Launch; v2, final");
+        parent.tags = vec!["work,urgent".to_string(), "q3".to_string()];
+        parent.status = ItemStatus::Blocked;
+        let mut child = Item::new(ItemType::Milestone, "Beta");
+        child.depends_on.push(parent.id.clone());
+        child.status = ItemStatus::Complete;
+        parent.sub_items.push(child);
+        list.items.push(parent);
+        let imported = import("calendar", &export(&list));
+        let launch = &imported.items[0];
+        assert_eq!(launch.text, "Launch; v2, final");
+        assert_eq!(launch.tags, list.items[0].tags);
+        assert!(launch.status.eq(&ItemStatus::Blocked));
+        let beta = &launch.sub_items[0];
+        assert!(beta.item_type.eq(&ItemType::Milestone));
+        assert!(beta.status.eq(&ItemStatus::Complete));
+        assert_eq!(beta.depends_on, vec![launch.id.clone()]);
+    }
+}
@@ -1,27 +1,38 @@
 use {
     chrono::{
         DateTime,
+        Duration,
         Local,
     },
     crate::{
+        effort::EffortTotals,
+        filter::{ Filter, FilterContext, },
         enums::{
-            PrintWhich,
+            Estimate,
             ItemStatus,
             ItemType,
+            PrintWhich,
+            Recurrence,
         },
-        utils::styler,
+        print_options::{ PrintContext, PrintOptions, },
+        time_entry::TimeEntry,
+        utils::{ generate_id, styler, },
     },
     serde::{
         Deserialize,
         Serialize,
     },
+    serde_json::Value,
     std::{
+        collections::{ BTreeMap, HashSet, },
         io::Error as IOError,
-        ops::Add,
+        mem,
     },
 };
-#[derive(Serialize, Deserialize, PartialEq)]
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct Item {
+    #[serde(default)]
+    pub id: String,
     pub item_type: ItemType,
     pub status: ItemStatus,
     pub text: String,
@@ -29,11 +40,38 @@ pub struct Item {
     pub created: DateTime<Local>,
     pub last_updated: DateTime<Local>,
     pub hidden: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_entries: Vec<TimeEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_from: Option<Vec<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub occurrences: Vec<Item>,
 }
 impl Item {
     pub fn new(item_type: ItemType, text: impl AsRef<str>) -> Self {
         let txt = text.as_ref().to_string();
         Self {
+            id: generate_id(),
             item_type,
             status: ItemStatus::Incomplete,
             text: txt,
@@ -41,29 +79,57 @@ impl Item {
             created: Local::now(),
             last_updated: Local::now(),
             hidden: false,
+            tags: Vec::new(),
+            priority: None,
+            due: None,
+            recurrence: None,
+            time_entries: Vec::new(),
+            estimate: None,
+            depends_on: Vec::new(),
+            link: None,
+            fields: BTreeMap::new(),
+            assignees: Vec::new(),
+            archived_from: None,
+            archived_parent: None,
+            occurrences: Vec::new(),
         }
     }
-    fn has_complete(&self) -> bool {
-        if self.item_type.eq(&ItemType::Todo) && self.status.eq(&ItemStatus::Complete) {
-            return true;
-        }
-        for item in self.sub_items.iter() {
-            if item.has_complete() {
-                return true;
-            }
+    pub fn reassign_ids(&mut self) {
+        self.id = generate_id();
+        for sub in self.sub_items.iter_mut() {
+            sub.reassign_ids();
         }
-        return false;
     }
-    fn has_incomplete(&self) -> bool {
-        if self.item_type.eq(&ItemType::Todo) && self.status.eq(&ItemStatus::Incomplete) {
-            return true;
+    fn reset(&mut self, now: DateTime<Local>) {
+        self.status = ItemStatus::Incomplete;
+        self.created = now;
+        self.last_updated = now;
+        self.hidden = false;
+        self.time_entries.clear();
+        self.occurrences.clear();
+        for sub in self.sub_items.iter_mut() {
+            sub.reset(now);
         }
-        for item in self.sub_items.iter() {
-            if item.has_incomplete() {
-                return true;
-            }
-        }
-        return false;
+    }
+    pub fn next_occurrence(&self, now: DateTime<Local>) -> Option<Self> {
+        let recurrence = self.recurrence.as_ref()?;
+        let mut next = self.clone();
+        next.reset(now);
+        next.due = Some(recurrence.next_due(self.due, now));
+        Some(next)
+    }
+    pub fn roll_over(&mut self, now: DateTime<Local>) -> bool {
+        let next = match self.next_occurrence(now) {
+            Some(next) => next,
+            None => return false,
+        };
+        let mut completed = mem::replace(self, next);
+        self.occurrences = mem::take(&mut completed.occurrences);
+        completed.reassign_ids();
+        completed.recurrence = None;
+        completed.last_updated = now;
+        self.occurrences.push(completed);
+        true
     }
     pub fn get_highest_num(&self, mut cmp: usize) {
         let highest_num = self.sub_items.len() + 1;
@@ -78,130 +144,358 @@ impl Item {
         let mut s = String::new();
         let i_len = index.to_string().len();
         for _ in 0..(spacing - i_len) {
-            s.push_str(" ");
+            s.push(' ');
         }
-        return s;
+        s
     }
-    pub fn printable(
-        &self, output: &mut String, index: &mut usize, level: &mut usize,
-        print_which: &PrintWhich, plain: bool, spacing: usize,
-        max_level: Option<usize>, parent_is_hidden: bool, display_hidden: bool,
-    ) -> Result<(), IOError> {
-        match print_which {
-            PrintWhich::All => {},
-            PrintWhich::Complete => {
-                if !self.has_complete() {
-                    return Ok(());
-                }
-            },
-            PrintWhich::Incomplete => {
-                if !self.has_incomplete() {
-                    return Ok(());
-                }
-            },
+    pub fn has_running_timer(&self) -> bool {
+        self.time_entries.iter().any(|entry| entry.is_running())
+    }
+    pub fn start_timer(&mut self, note: Option<String>) {
+        if !self.has_running_timer() {
+            self.time_entries.push(TimeEntry::new(note));
         }
+    }
+    pub fn stop_timer(&mut self) {
+        for entry in self.time_entries.iter_mut() {
+            if entry.is_running() {
+                entry.end = Some(Local::now());
+            }
+        }
+    }
+    pub fn logged_time(&self) -> Duration {
+        self.time_entries.iter().fold(Duration::zero(), |total, entry| {
+            total + entry.duration()
+        })
+    }
+    pub fn total_logged_time(&self) -> Duration {
+        self.sub_items.iter().chain(self.occurrences.iter())
+            .fold(self.logged_time(), |total, sub| total + sub.total_logged_time())
+    }
+    pub fn effort(&self, context: &FilterContext) -> EffortTotals {
+        let mut totals = EffortTotals::default();
+        for sub in self.sub_items.iter() {
+            totals.add(&sub.effort(context));
+        }
+        if !self.item_type.is_actionable() {
+            return totals;
+        }
+        if let Some(estimate) = &self.estimate {
+            if context.is_done(&self.status) {
+                totals.completed.add_estimate(estimate);
+            } else if context.is_open(&self.status) {
+                totals.remaining.add_estimate(estimate);
+            }
+        }
+        totals
+    }
+    pub fn field_text(&self, key: &str) -> Option<String> {
+        match self.fields.get(key)? {
+            Value::String(value) => Some(value.clone()),
+            value => Some(value.to_string()),
+        }
+    }
+    fn get_indent(level: usize, spacing: usize) -> String {
         let mut indent = String::new();
-        for _ in 0..level.clone() {
+        for _ in 0..level {
             for _ in 0..spacing {
-                indent.push_str(" ");
+                indent.push(' ');
             }
             indent.push_str("      ");
         }
-        let show_this = (!self.hidden && !parent_is_hidden) || display_hidden;
-        if show_this {
-            match self.item_type {
-                ItemType::Todo => {
-                    if !plain {
-                        let status = format!(
-                            "{}. {}[{}]",
-                            index,
-                            Self::get_spacing(*index, spacing),
-                            self.status.symbol(),
-                        );
-                        let status_line = styler::bold(
-                            format!("\n{}{} ", indent, status)
-                        );
-                        match self.status.clone() {
-                            ItemStatus::Complete => {
-                                output.push_str(&styler::success(status_line));
-                            },
-                            ItemStatus::Disabled => {
-                                output.push_str(&styler::warning(status_line));
-                            },
-                            ItemStatus::Incomplete => {
-                                output.push_str(&styler::danger(status_line));
-                            },
-                        }
-                        output.push_str(&format!("{}", self.text));
-                    } else {
-                        output.push_str(&format!(
-                            "\n{}{}. {}[{}] {}",
-                            indent,
-                            index,
-                            Self::get_spacing(*index, spacing),
-                            self.status.symbol(),
-                            self.text
-                        ));
+        indent
+    }
+    fn priority_prefix(&self) -> String {
+        match self.priority {
+            Some(priority) => format!("({}) ", priority),
+            None => String::new(),
+        }
+    }
+    fn details_suffix(&self) -> String {
+        let mut suffix = String::new();
+        if let Some(estimate) = &self.estimate {
+            suffix.push_str(&format!(" (est {})", estimate));
+        }
+        if let Some(due) = self.due {
+            suffix.push_str(&format!(" (due {})", due.format("%m/%d/%Y")));
+        }
+        if !self.assignees.is_empty() {
+            let assignees = self.assignees.iter()
+                .map(|assignee| format!("@{}", assignee))
+                .collect::<Vec<String>>();
+            suffix.push_str(&format!(" ({})", assignees.join(", ")));
+        }
+        suffix
+    }
+    pub fn print_line(
+        &self, output: &mut String, index: usize, level: usize,
+        context: &PrintContext,
+    ) {
+        let plain = context.plain;
+        let spacing = context.spacing;
+        let blocked = context.blocked.contains(&self.id);
+        let indent = Self::get_indent(level, spacing);
+        let (open, close) = match self.item_type {
+            ItemType::Milestone => ('<', '>'),
+            _ => ('[', ']'),
+        };
+        match self.item_type {
+            ItemType::Todo | ItemType::Milestone => {
+                if !plain {
+                    let status = format!(
+                        "{}. {}{}{}{}",
+                        index,
+                        Self::get_spacing(index, spacing),
+                        open,
+                        context.filter.symbol(&self.status),
+                        close,
+                    );
+                    let status_line = styler::bold(
+                        format!("\n{}{} ", indent, status)
+                    );
+                    let defined_color = context.filter.definition(&self.status)
+                        .and_then(|definition| definition.color.as_ref())
+                        .and_then(|color| styler::color(&status_line, color));
+                    match self.status.clone() {
+                        _ if blocked => {
+                            output.push_str(&styler::accent(status_line));
+                        },
+                        _ if defined_color.is_some() => {
+                            output.push_str(&defined_color.unwrap());
+                        },
+                        ItemStatus::Complete => {
+                            output.push_str(&styler::success(status_line));
+                        },
+                        ItemStatus::Disabled => {
+                            output.push_str(&styler::warning(status_line));
+                        },
+                        ItemStatus::Incomplete => {
+                            output.push_str(&styler::danger(status_line));
+                        },
+                        ItemStatus::InProgress => {
+                            output.push_str(&styler::primary(status_line));
+                        },
+                        ItemStatus::Blocked => {
+                            output.push_str(&styler::accent(status_line));
+                        },
+                        ItemStatus::Waiting => {
+                            output.push_str(&styler::muted(status_line));
+                        },
+                        ItemStatus::Custom(_) => {
+                            output.push_str(&styler::info(status_line));
+                        },
                     }
-                },
-                ItemType::Note => {
-                    if !plain {
-                        let status = format!("\n{}{}. ", indent, index);
-                        let status_line = styler::bold(status);
-                        output.push_str(&styler::info(status_line));
-                        output.push_str(&format!(
-                            "{}    {}",
-                            Self::get_spacing(*index, spacing),
-                            self.text
-                        ));
-                    } else {
-                        output.push_str(&format!(
-                            "\n{}{}. {}    {}",
-                            indent,
-                            index,
-                            Self::get_spacing(*index, spacing),
-                            self.text
-                        ));
+                    output.push_str(&self.priority_prefix());
+                    output.push_str(&self.text);
+                    if blocked {
+                        output.push_str(&styler::accent(styler::bold(" (blocked)")));
                     }
-                },
-            }
-        }
-        match max_level {
-            Some(max) => {
-                if (*level).eq(&(max - 1)) {
-                    return Ok(());
+                    output.push_str(&styler::italic(self.details_suffix()));
+                } else {
+                    output.push_str(&format!(
+                        "\n{}{}. {}{}{}{} {}{}{}{}",
+                        indent,
+                        index,
+                        Self::get_spacing(index, spacing),
+                        open,
+                        context.filter.symbol(&self.status),
+                        close,
+                        self.priority_prefix(),
+                        self.text,
+                        if blocked { " (blocked)" } else { "" },
+                        self.details_suffix(),
+                    ));
+                }
+            },
+            ItemType::Note => {
+                if !plain {
+                    let status = format!("\n{}{}. ", indent, index);
+                    let status_line = styler::bold(status);
+                    output.push_str(&styler::info(status_line));
+                    output.push_str(&format!(
+                        "{}    {}",
+                        Self::get_spacing(index, spacing),
+                        self.text
+                    ));
+                } else {
+                    output.push_str(&format!(
+                        "\n{}{}. {}    {}",
+                        indent,
+                        index,
+                        Self::get_spacing(index, spacing),
+                        self.text
+                    ));
+                }
+            },
+            ItemType::Heading => {
+                let heading = format!(
+                    "\n{}{}. {}# {}",
+                    indent,
+                    index,
+                    Self::get_spacing(index, spacing),
+                    self.text
+                );
+                if !plain {
+                    output.push_str(&styler::primary(styler::bold(heading)));
+                } else {
+                    output.push_str(&heading);
+                }
+            },
+            ItemType::Link => {
+                let link = match &self.link {
+                    Some(link) => format!(" <{}>", link),
+                    None => String::new(),
+                };
+                if !plain {
+                    let status = format!("\n{}{}. ", indent, index);
+                    let status_line = styler::bold(status);
+                    output.push_str(&styler::info(status_line));
+                    output.push_str(&format!(
+                        "{}  @ {}",
+                        Self::get_spacing(index, spacing),
+                        self.text
+                    ));
+                    output.push_str(&styler::info(styler::italic(link)));
+                } else {
+                    output.push_str(&format!(
+                        "\n{}{}. {}  @ {}{}",
+                        indent,
+                        index,
+                        Self::get_spacing(index, spacing),
+                        self.text,
+                        link
+                    ));
                 }
             },
-            None => {},
         }
-        let mut sub_index = 1;
-        for sub in self.sub_items.iter() {
-            sub.printable(
-                output, &mut sub_index, &mut (level.add(1)), print_which,
-                plain, spacing, max_level, !show_this, display_hidden,
+    }
+    pub fn print_fields(&self, output: &mut String, level: usize, context: &PrintContext) {
+        let indent = Self::get_indent(level + 1, context.spacing);
+        for key in self.fields.keys() {
+            let value = self.field_text(key).unwrap_or_default();
+            if !context.plain {
+                output.push_str(&format!("\n{}", indent));
+                output.push_str(&styler::muted(styler::bold(format!("{}: ", key))));
+                output.push_str(&styler::italic(value));
+            } else {
+                output.push_str(&format!("\n{}{}: {}", indent, key, value));
+            }
+        }
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub fn printable(
+        &self, output: &mut String, index: &mut usize, level: &mut usize,
+        print_which: &PrintWhich, plain: bool, spacing: usize,
+        max_level: Option<usize>, parent_is_hidden: bool, display_hidden: bool,
+    ) -> Result<(), IOError> {
+        let options = PrintOptions::new(
+            Filter::from(print_which), plain, max_level, display_hidden,
+        );
+        let context = PrintContext {
+            plain,
+            spacing,
+            blocked: HashSet::new(),
+            filter: FilterContext::default(),
+        };
+        self.print_tree(output, *index, *level, parent_is_hidden, &options, &context)
+    }
+    pub fn print_tree(
+        &self, output: &mut String, index: usize, level: usize,
+        parent_is_hidden: bool, options: &PrintOptions, context: &PrintContext,
+    ) -> Result<(), IOError> {
+        if !options.filter.matches_tree(self, &context.filter) {
+            return Ok(());
+        }
+        let show_this = (!self.hidden && !parent_is_hidden) || options.display_hidden;
+        if show_this {
+            self.print_line(output, index, level, context);
+            if options.verbose {
+                self.print_fields(output, level, context);
+            }
+        }
+        if let Some(max) = options.max_level {
+            if level.eq(&(max - 1)) {
+                return Ok(());
+            }
+        }
+        let order = match &options.sort {
+            Some(sort) if sort.recursive => sort.order(&self.sub_items),
+            _ => (0..self.sub_items.len()).collect(),
+        };
+        for i in order {
+            self.sub_items[i].print_tree(
+                output, i + 1, level + 1, !show_this, options, context,
             )?;
-            sub_index = sub_index + 1;
         }
         Ok(())
     }
     pub fn count_complete(&self) -> usize {
         let mut counter = 0;
-        if self.item_type.eq(&ItemType::Todo) && self.status.eq(&ItemStatus::Complete) {
-            counter = counter + 1;
+        if self.item_type.is_actionable() && self.status.eq(&ItemStatus::Complete) {
+            counter += 1;
         }
         for sub in self.sub_items.iter() {
-            counter = sub.count_complete();
+            counter += sub.count_complete();
+        }
+        counter
+    }
+    pub fn count_status(&self, status: &ItemStatus) -> usize {
+        let mut counter = 0;
+        if self.item_type.is_actionable() && self.status.eq(status) {
+            counter += 1;
+        }
+        for sub in self.sub_items.iter() {
+            counter += sub.count_status(status);
         }
         counter
     }
     pub fn count_incomplete(&self) -> usize {
         let mut counter = 0;
-        if self.item_type.eq(&ItemType::Todo) && !self.status.eq(&ItemStatus::Incomplete) {
-            counter = counter + 1;
+        if self.item_type.is_actionable() && self.status.eq(&ItemStatus::Incomplete) {
+            counter += 1;
         }
         for sub in self.sub_items.iter() {
-            counter = sub.count_incomplete();
+            counter += sub.count_incomplete();
         }
         counter
     }
 }
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_utils::todo,
+    };
+    #[test]
+    fn counts_include_every_descendant() {
+        let mut parent = todo("parent", ItemStatus::Incomplete);
+        let mut child = todo("child", ItemStatus::Complete);
+        child.sub_items.push(todo("grandchild", ItemStatus::Complete));
+        parent.sub_items.push(child);
+        parent.sub_items.push(todo("open", ItemStatus::Incomplete));
+        parent.sub_items.push(todo("started", ItemStatus::InProgress));
+        parent.sub_items.push(Item::new(ItemType::Note, "note"));
+        assert_eq!(parent.count_complete(), 2);
+        assert_eq!(parent.count_incomplete(), 2);
+        assert_eq!(parent.count_status(&ItemStatus::InProgress), 1);
+    }
+    #[test]
+    fn effort_adds_a_parents_own_estimate_to_its_children() {
+        let context = FilterContext::default();
+        let mut parent = todo("parent", ItemStatus::Incomplete);
+        parent.estimate = Some(Estimate::Minutes(30));
+        let mut done = todo("done", ItemStatus::Complete);
+        done.estimate = Some(Estimate::Minutes(60));
+        let mut open = todo("open", ItemStatus::Incomplete);
+        open.estimate = Some(Estimate::Points(2_f64));
+        parent.sub_items.push(done);
+        parent.sub_items.push(open);
+        let mut note = Item::new(ItemType::Note, "note");
+        note.estimate = Some(Estimate::Minutes(600));
+        parent.sub_items.push(note);
+        let totals = parent.effort(&context);
+        assert_eq!(totals.remaining.minutes, 30);
+        assert_eq!(totals.remaining.points, 2_f64);
+        assert_eq!(totals.completed.minutes, 60);
+    }
+}
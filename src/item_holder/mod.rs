@@ -1,13 +1,27 @@
+use chrono::DateTime;
 use chrono::Local;
-use crate::container::Container;
+use crate::enums::Estimate;
+use crate::enums::ExitCode;
 use crate::enums::ItemStatus;
 use crate::enums::ItemType;
+use crate::enums::Recurrence;
 use crate::item::Item;
 use crate::list::List;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
 use std::borrow::BorrowMut;
 pub trait ItemHolder {
     fn update_date(&mut self);
     fn borrow_items_mut(&mut self) -> &mut Vec<Item>;
+    fn prepare_action(&self, _indices: &[usize], action: ItemAction) -> ItemAction {
+        action
+    }
+    fn resolve_action(
+        &self, indices: &[usize], action: ItemAction,
+    ) -> Result<ItemAction, ExitCode> {
+        Ok(self.prepare_action(indices, action))
+    }
 }
 impl ItemHolder for Item {
     fn update_date(&mut self) {
@@ -24,15 +38,44 @@ impl ItemHolder for List {
     fn borrow_items_mut(&mut self) -> &mut Vec<Item> {
         self.items.borrow_mut()
     }
+    fn prepare_action(&self, indices: &[usize], action: ItemAction) -> ItemAction {
+        let action = match action {
+            ItemAction::CycleStatus if !self.status_cycle.is_empty() => {
+                ItemAction::CycleStatusIn(self.status_cycle.clone())
+            },
+            ItemAction::Put(item) => {
+                ItemAction::Put(Box::new(self.with_unique_ids(*item)))
+            },
+            _ => action,
+        };
+        roll_over_when_done(self, indices, action)
+    }
+    fn resolve_action(
+        &self, indices: &[usize], action: ItemAction,
+    ) -> Result<ItemAction, ExitCode> {
+        self.validate_action(indices, &action)?;
+        Ok(self.prepare_action(indices, action))
+    }
 }
-impl ItemHolder for Container {
-    fn update_date(&mut self) {
-        self.list.last_updated = Local::now();
+fn roll_over_when_done(list: &List, indices: &[usize], action: ItemAction) -> ItemAction {
+    let alters_status = action.dirty_eq(&ItemAction::CycleStatus)
+        || action.dirty_eq(&ItemAction::CycleStatusIn(Vec::new()))
+        || action.dirty_eq(&ItemAction::AlterStatus(ItemStatus::Complete));
+    if !alters_status || indices.is_empty() {
+        return action;
     }
-    fn borrow_items_mut(&mut self) -> &mut Vec<Item> {
-        self.list.items.borrow_mut()
+    let path = indices.iter().rev().copied().collect::<Vec<usize>>();
+    let mut item = match list.get_item(&path) {
+        Some(item) if item.recurrence.is_some() => item.clone(),
+        _ => return action,
+    };
+    item.act_on_item(&mut Vec::new(), action.clone());
+    if !list.filter_context().is_done(&item.status) || !item.roll_over(Local::now()) {
+        return action;
     }
+    ItemAction::Replace(Box::new(item))
 }
+#[derive(Serialize, Deserialize, Clone)]
 pub enum ItemAction {
     AlterStatus(ItemStatus),
     AlterHidden(bool),
@@ -41,8 +84,25 @@ pub enum ItemAction {
     CycleStatus,
     Edit(String),
     Remove,
-    Put(Item),
-    ToggleType,
+    Put(Box<Item>),
+    SetType(ItemType),
+    AddTag(String),
+    RemoveTag(String),
+    SetPriority(Option<u8>),
+    SetDue(Option<DateTime<Local>>),
+    SetRecurrence(Option<Recurrence>),
+    StartTimer(Option<String>),
+    StopTimer,
+    SetEstimate(Option<Estimate>),
+    AddDependency(String),
+    RemoveDependency(String),
+    CycleStatusIn(Vec<ItemStatus>),
+    SetLink(Option<String>),
+    SetField(String, Value),
+    UnsetField(String),
+    Assign(String),
+    Unassign(String),
+    Replace(Box<Item>),
 }
 impl ItemAction {
     fn to_int(&self) -> i8 {
@@ -55,7 +115,24 @@ impl ItemAction {
             Self::Put(_) => 6,
             Self::CycleStatus => 7,
             Self::ToggleHidden => 8,
-            Self::ToggleType => 9,
+            Self::SetType(_) => 9,
+            Self::AddTag(_) => 10,
+            Self::RemoveTag(_) => 11,
+            Self::SetPriority(_) => 12,
+            Self::SetDue(_) => 13,
+            Self::SetRecurrence(_) => 14,
+            Self::StartTimer(_) => 15,
+            Self::StopTimer => 16,
+            Self::SetEstimate(_) => 17,
+            Self::AddDependency(_) => 18,
+            Self::RemoveDependency(_) => 19,
+            Self::CycleStatusIn(_) => 20,
+            Self::SetLink(_) => 21,
+            Self::SetField(_, _) => 22,
+            Self::UnsetField(_) => 23,
+            Self::Assign(_) => 24,
+            Self::Unassign(_) => 25,
+            Self::Replace(_) => 26,
         }
     }
     fn dirty_eq(&self, rhs: &Self) -> bool {
@@ -67,7 +144,7 @@ trait ActOnItem {
 }
 impl ActOnItem for Item {
     fn act_on_item(&mut self, indices: &mut Vec<usize>, action: ItemAction) -> Option<Item> {
-        if indices.len() == 0 {
+        if indices.is_empty() {
             match action {
                 ItemAction::AlterStatus(status) => {
                     self.status = status;
@@ -83,36 +160,87 @@ impl ActOnItem for Item {
                 },
                 ItemAction::Remove => {},
                 ItemAction::Put(item) => {
-                    self.sub_items.push(item);
+                    self.sub_items.push(*item);
                 },
                 ItemAction::CycleStatus => {
-                    let next_status = match self.status {
-                        ItemStatus::Complete => ItemStatus::Disabled,
-                        ItemStatus::Disabled => ItemStatus::Incomplete,
-                        ItemStatus::Incomplete => ItemStatus::Complete,
-                    };
-                    self.status = next_status;
-                },
-                ItemAction::ToggleType => {
-                    let next_type = match self.item_type {
-                        ItemType::Todo => ItemType::Note,
-                        ItemType::Note => ItemType::Todo,
-                    };
-                    self.item_type = next_type;
+                    self.status = self.status.next_in(&ItemStatus::default_cycle());
+                },
+                ItemAction::CycleStatusIn(cycle) => {
+                    self.status = self.status.next_in(&cycle);
+                },
+                ItemAction::SetType(item_type) => {
+                    self.item_type = item_type;
                 },
                 ItemAction::ToggleHidden => {
                     self.hidden = !self.hidden;
                 },
+                ItemAction::AddTag(tag) => {
+                    if !self.tags.contains(&tag) {
+                        self.tags.push(tag);
+                    }
+                },
+                ItemAction::RemoveTag(tag) => {
+                    self.tags.retain(|t| t.ne(&tag));
+                },
+                ItemAction::SetPriority(priority) => {
+                    self.priority = priority;
+                },
+                ItemAction::SetDue(due) => {
+                    self.due = due;
+                },
+                ItemAction::SetRecurrence(recurrence) => {
+                    self.recurrence = recurrence;
+                },
+                ItemAction::StartTimer(note) => {
+                    self.start_timer(note);
+                },
+                ItemAction::StopTimer => {
+                    self.stop_timer();
+                },
+                ItemAction::SetEstimate(estimate) => {
+                    self.estimate = estimate;
+                },
+                ItemAction::AddDependency(id) => {
+                    if !self.depends_on.contains(&id) {
+                        self.depends_on.push(id);
+                    }
+                },
+                ItemAction::RemoveDependency(id) => {
+                    self.depends_on.retain(|dependency| dependency.ne(&id));
+                },
+                ItemAction::SetLink(link) => {
+                    self.link = link;
+                },
+                ItemAction::SetField(key, value) => {
+                    self.fields.insert(key, value);
+                },
+                ItemAction::UnsetField(key) => {
+                    self.fields.remove(&key);
+                },
+                ItemAction::Assign(assignee) => {
+                    if !self.assignees.contains(&assignee) {
+                        self.assignees.push(assignee);
+                    }
+                },
+                ItemAction::Unassign(assignee) => {
+                    self.assignees.retain(|a| a.ne(&assignee));
+                },
+                ItemAction::Replace(item) => {
+                    *self = *item;
+                },
             }
             self.update_date();
-            return None;
+            None
         } else {
             self.update_date();
-            return self.act_on_item_at(indices, action);
+            self.act_on_item_at(indices, action)
         }
     }
 }
 pub trait ItemActor {
+    fn try_act_on_item_at(
+        &mut self, indices: &mut Vec<usize>, action: ItemAction
+    ) -> Result<Option<Item>, ExitCode>;
     fn act_on_item_at(
         &mut self, indices: &mut Vec<usize>, action: ItemAction
     ) -> Option<Item>;
@@ -121,43 +249,63 @@ impl<Holder> ItemActor for Holder
 where
     Holder: ItemHolder
 {
+    fn try_act_on_item_at(
+        &mut self, indices: &mut Vec<usize>, action: ItemAction
+    ) -> Result<Option<Item>, ExitCode> {
+        let action = self.resolve_action(indices, action)?;
+        Ok(apply_action(self, indices, action))
+    }
     fn act_on_item_at(
         &mut self, indices: &mut Vec<usize>, action: ItemAction
     ) -> Option<Item> {
-        if indices.len() == 0 {
-            match action {
-                ItemAction::Put(item) => {
-                    self.update_date();
-                    let items = self.borrow_items_mut();
-                    items.push(item);
-                    return None;
-                },
-                ItemAction::Add(item_type, message) => {
-                    self.update_date();
-                    let items = self.borrow_items_mut();
-                    items.push(Item::new(item_type, message));
-                    return None;
-                },
-                _ => return None,
-            }
-        } else if action.dirty_eq(&ItemAction::Remove) && indices.len() == 1 {
-            self.update_date();
-            let items = self.borrow_items_mut();
-            let remove_index = indices.pop().unwrap();
-            let mut item = items.remove(remove_index - 1);
-            item.last_updated = Local::now();
-            return Some(item);
+        let action = self.prepare_action(indices, action);
+        apply_action(self, indices, action)
+    }
+}
+fn apply_action(
+    holder: &mut impl ItemHolder, indices: &mut Vec<usize>, action: ItemAction,
+) -> Option<Item> {
+    if indices.is_empty() {
+        match action {
+            ItemAction::Put(item) => {
+                holder.update_date();
+                let items = holder.borrow_items_mut();
+                items.push(*item);
+                return None;
+            },
+            ItemAction::Add(item_type, message) => {
+                holder.update_date();
+                let items = holder.borrow_items_mut();
+                items.push(Item::new(item_type, message));
+                return None;
+            },
+            _ => return None,
         }
-        let items = self.borrow_items_mut();
-        let item_index = indices.pop().unwrap();
-        for i in 1..(items.len() + 1) {
-            if i == item_index {
-                let item = items.get_mut(i - 1).unwrap();
-                let out_item = item.act_on_item(indices, action);
-                self.update_date();
-                return out_item;
+    } else if action.dirty_eq(&ItemAction::Remove) && indices.len() == 1 {
+        holder.update_date();
+        let items = holder.borrow_items_mut();
+        let remove_index = indices.pop().unwrap();
+        let mut item = items.remove(remove_index - 1);
+        item.last_updated = Local::now();
+        return Some(item);
+    }
+    let items = holder.borrow_items_mut();
+    let item_index = indices.pop().unwrap();
+    let alters_status = indices.is_empty() && (
+        action.dirty_eq(&ItemAction::CycleStatus)
+        || action.dirty_eq(&ItemAction::CycleStatusIn(Vec::new()))
+        || action.dirty_eq(&ItemAction::AlterStatus(ItemStatus::Complete))
+    );
+    for i in 1..(items.len() + 1) {
+        if i == item_index {
+            let item = items.get_mut(i - 1).unwrap();
+            let out_item = item.act_on_item(indices, action);
+            if alters_status && item.status.eq(&ItemStatus::Complete) {
+                item.roll_over(Local::now());
             }
+            holder.update_date();
+            return out_item;
         }
-        return None;
     }
+    None
 }
@@ -0,0 +1,79 @@
+use {
+    chrono::{ DateTime, Local, },
+    crate::{
+        enums::ExitCode,
+        item_holder::ItemAction,
+        utils::current_user,
+    },
+    serde::{ Deserialize, Serialize, },
+    serde_json::{ from_str as from_json_string, to_string as to_json_string, },
+};
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub path: Vec<usize>,
+    pub action: ItemAction,
+}
+impl JournalEntry {
+    pub fn new(path: Vec<usize>, action: ItemAction) -> Self {
+        Self {
+            timestamp: Local::now(),
+            user: current_user(),
+            path,
+            action,
+        }
+    }
+    pub fn stamped_path(&self) -> &[usize] {
+        match self.action {
+            ItemAction::Remove => self.path.split_last().map_or(&[][..], |(_, p)| p),
+            _ => &self.path[..],
+        }
+    }
+    pub fn to_line(&self) -> Result<String, ExitCode> {
+        match to_json_string(self) {
+            Ok(json) => Ok(format!("{}\n", json)),
+            Err(e) => Err(ExitCode::FailedToSerialize(e)),
+        }
+    }
+    pub fn from_line(line: &str) -> Result<Self, ExitCode> {
+        match from_json_string(line) {
+            Ok(entry) => Ok(entry),
+            Err(e) => Err(ExitCode::FailedToDeserialize(e)),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn entries_round_trip_through_a_line() {
+        let mut entry = JournalEntry::new(vec![2, 1], ItemAction::Edit("renamed".to_string()));
+        entry.user = Some("sam".to_string());
+        let line = entry.to_line().unwrap();
+        assert!(line.ends_with('\n'));
+        assert_eq!(line.trim_end().lines().count(), 1);
+        let read = JournalEntry::from_line(line.trim_end()).unwrap();
+        assert_eq!(read.timestamp, entry.timestamp);
+        assert_eq!(read.user.as_deref(), Some("sam"));
+        assert_eq!(read.path, vec![2, 1]);
+        assert!(matches!(read.action, ItemAction::Edit(text) if text == "renamed"));
+        entry.user = None;
+        assert!(!entry.to_line().unwrap().contains("\"user\""));
+    }
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(matches!(
+            JournalEntry::from_line("{\"path\": [1]"),
+            Err(ExitCode::FailedToDeserialize(_))
+        ));
+    }
+    #[test]
+    fn removals_stamp_the_parent() {
+        assert_eq!(JournalEntry::new(vec![3, 2], ItemAction::Remove).stamped_path(), &[3]);
+        assert!(JournalEntry::new(vec![1], ItemAction::Remove).stamped_path().is_empty());
+        assert_eq!(JournalEntry::new(vec![3, 2], ItemAction::ToggleHidden).stamped_path(), &[3, 2]);
+        assert_eq!(JournalEntry::new(vec![], ItemAction::Edit("x".to_string())).stamped_path(), &[0; 0]);
+    }
+}
@@ -0,0 +1,54 @@
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct JsonStyle {
+    pub indent: usize,
+    pub trailing_newline: bool,
+}
+impl JsonStyle {
+    pub fn new(indent: usize, trailing_newline: bool) -> Self {
+        Self { indent, trailing_newline, }
+    }
+    pub fn compact() -> Self {
+        Self::new(0, false)
+    }
+    pub fn pretty() -> Self {
+        Self::new(2, true)
+    }
+    pub fn is_pretty(&self) -> bool {
+        self.indent > 0
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ enums::ItemType, item::Item, list::List, },
+        serde_json::Value,
+    };
+    fn sample() -> List {
+        let mut list = List::new("style".to_string());
+        let mut item = Item::new(ItemType::Todo, "write");
+        item.fields.insert("zeta".to_string(), Value::from(1));
+        item.fields.insert("alpha".to_string(), Value::from(2));
+        list.items.push(item);
+        list
+    }
+    #[test]
+    fn compact_style_matches_to_json() {
+        let list = sample();
+        assert_eq!(list.to_json_with(&JsonStyle::compact()).unwrap(), list.to_json().unwrap());
+        let json = list.to_json_with(&JsonStyle::new(0, true)).unwrap();
+        assert_eq!(json.lines().count(), 1);
+        assert!(json.ends_with("}\n"));
+    }
+    #[test]
+    fn pretty_style_indents_and_keeps_field_order() {
+        let list = sample();
+        let json = list.to_json_with(&JsonStyle::new(4, true)).unwrap();
+        assert!(json.starts_with("{\n    \"name\": \"style\",\n"));
+        assert!(json.ends_with("}\n"));
+        assert!(json.find("\"alpha\"").unwrap() < json.find("\"zeta\"").unwrap());
+        assert_eq!(json, list.to_json_with(&JsonStyle::new(4, true)).unwrap());
+        let reloaded = List::from_json(json).unwrap();
+        assert_eq!(reloaded.to_json().unwrap(), list.to_json().unwrap());
+    }
+}
@@ -1,17 +1,55 @@
 mod args;
 mod container;
+mod effort;
 mod traits;
 mod enums;
+mod filter;
 mod item;
 mod item_holder;
+mod icalendar;
+mod journal;
+mod json_style;
 mod list;
+mod markdown;
+mod org;
+mod print_options;
+mod scanner;
+mod search;
+mod sort;
+mod status_definition;
+mod table;
+mod storage;
+#[cfg(test)]
+mod test_utils;
+mod time_entry;
+mod todo_txt;
 mod utils;
 pub use {
     container::Container,
-    enums::{ ExitCode, ItemStatus, ItemType, PathExitCondition, PrintWhich, },
+    effort::{ Effort, EffortTotals, },
+    enums::{
+        Estimate, ExitCode, ItemRef, ItemStatus, ItemType, PathExitCondition,
+        ListFormat, PrintWhich, Recurrence, SearchMode, SortKey, StorageMode,
+        TableFormat, TodoTxtNesting,
+    },
+    filter::{ Comparison, Filter, FilterContext, ParseFilterError, },
     item::Item,
     item_holder::{ ItemAction, ItemActor, },
+    journal::JournalEntry,
+    json_style::JsonStyle,
     list::List,
+    print_options::PrintOptions,
+    scanner::{ scan_comments, Scan, ScanOptions, SyncReport, TodoComment, },
+    search::SearchMatch,
+    sort::SortOptions,
+    status_definition::StatusDefinition,
+    storage::{ FileStorage, MemoryStorage, Storage, },
+    time_entry::TimeEntry,
     traits::GetPath,
-    utils::get_printable_coords,
+    utils::{
+        current_user, date_to_local, format_duration, generate_id,
+        get_printable_coords,
+    },
 };
+#[cfg(feature = "sqlite")]
+pub use storage::SqliteStorage;
@@ -1,16 +1,51 @@
 use {
-    chrono::{ DateTime, Local, },
-    crate::{ enums::{ ExitCode, PrintWhich, }, item::Item, utils::styler, },
+    chrono::{ DateTime, Duration, Local, NaiveDate, },
+    crate::{
+        effort::EffortTotals,
+        enums::{
+            ExitCode, ItemRef, ItemStatus, ListFormat, PrintWhich,
+            SearchMode, TableFormat, TodoTxtNesting,
+        },
+        filter::{ Filter, FilterContext, ParseFilterError, },
+        item::Item,
+        item_holder::{ ItemAction, ItemActor, ItemHolder, },
+        icalendar,
+        json_style::JsonStyle,
+        markdown,
+        org,
+        print_options::{ PrintContext, PrintOptions, },
+        scanner::{ self, Scan, ScanOptions, SyncReport, },
+        search::{ Search, SearchMatch, },
+        sort::SortOptions,
+        status_definition::StatusDefinition,
+        table,
+        todo_txt,
+        utils::{ current_user, format_duration, generate_id, get_printable_coords, styler, },
+    },
     serde::{ Deserialize, Serialize, },
-    serde_json::{ from_str as from_json_string, to_string as to_json_string, },
-    std::{ io::Error as IOError, ops::Add, },
+    serde_json::{
+        from_str as from_json_string,
+        ser::PrettyFormatter,
+        to_string as to_json_string,
+        Serializer,
+        Value,
+    },
+    std::{
+        collections::{ BTreeMap, HashMap, HashSet, },
+        io::Error as IOError,
+        path::Path,
+    },
 };
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct List {
     pub name: String,
     pub items: Vec<Item>,
     pub created: DateTime<Local>,
     pub last_updated: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_cycle: Vec<ItemStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<StatusDefinition>,
 }
 impl List {
     pub fn new(name: String) -> Self {
@@ -19,13 +54,16 @@ impl List {
             items: Vec::new(),
             created: Local::now(),
             last_updated: Local::now(),
+            status_cycle: Vec::new(),
+            statuses: Vec::new(),
         }
     }
     pub fn from_json(json: String) -> Result<Self, ExitCode> {
-        let list = match from_json_string(&json) {
+        let mut list: Self = match from_json_string(&json) {
             Ok(list) => list,
             Err(e) => return Err(ExitCode::FailedToDeserialize(e)),
         };
+        list.define_missing_statuses();
         Ok(list)
     }
     pub fn to_json(&self) -> Result<String, ExitCode> {
@@ -35,6 +73,91 @@ impl List {
         };
         Ok(json)
     }
+    pub fn to_json_with(&self, style: &JsonStyle) -> Result<String, ExitCode> {
+        if !style.is_pretty() {
+            let mut json = self.to_json()?;
+            if style.trailing_newline {
+                json.push('\n');
+            }
+            return Ok(json);
+        }
+        let indent = " ".repeat(style.indent);
+        let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+        let mut bytes = Vec::new();
+        let mut serializer = Serializer::with_formatter(&mut bytes, formatter);
+        if let Err(e) = self.serialize(&mut serializer) {
+            return Err(ExitCode::FailedToSerialize(e));
+        }
+        let mut json = String::from_utf8_lossy(&bytes).to_string();
+        if style.trailing_newline {
+            json.push('\n');
+        }
+        Ok(json)
+    }
+    pub fn from_icalendar(name: impl AsRef<str>, content: &str) -> Self {
+        icalendar::import(name, content)
+    }
+    pub fn to_icalendar(&self) -> String {
+        icalendar::export(self)
+    }
+    pub fn from_markdown(name: impl AsRef<str>, content: &str) -> Self {
+        markdown::import(name, content)
+    }
+    pub fn from_org(name: impl AsRef<str>, content: &str) -> Self {
+        org::import(name, content)
+    }
+    pub fn to_org(&self) -> String {
+        org::export(self)
+    }
+    pub fn sync_comments(&mut self, scan: &Scan) -> SyncReport {
+        scanner::sync(self, scan)
+    }
+    pub fn sync_source(
+        &mut self, root: &Path, options: &ScanOptions,
+    ) -> Result<SyncReport, ExitCode> {
+        let scan = scanner::scan_comments(root, options)?;
+        Ok(self.sync_comments(&scan))
+    }
+    pub fn from_table(name: impl AsRef<str>, content: &str, format: TableFormat) -> Self {
+        table::import(name, content, format)
+    }
+    pub fn to_table(&self, format: TableFormat) -> String {
+        table::export(self, format)
+    }
+    pub fn from_todo_txt(name: impl AsRef<str>, content: &str) -> Self {
+        todo_txt::import(name, content)
+    }
+    pub fn to_todo_txt(&self, nesting: TodoTxtNesting) -> String {
+        todo_txt::export(self, nesting)
+    }
+    pub fn from_format(content: String, format: ListFormat) -> Result<Self, ExitCode> {
+        let list: Result<Self, String> = match format {
+            ListFormat::Json(_) => return Self::from_json(content),
+            ListFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+            ListFormat::Yaml => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+        };
+        match list {
+            Ok(mut list) => {
+                list.define_missing_statuses();
+                Ok(list)
+            },
+            Err(e) => Err(ExitCode::FailedToDeserializeAs(format, e)),
+        }
+    }
+    pub fn to_format(&self, format: ListFormat) -> Result<String, ExitCode> {
+        let content = match format {
+            ListFormat::Json(style) => return self.to_json_with(&style),
+            ListFormat::Toml => match Self::find_null_field(&self.items) {
+                Some(key) => Err(format!("field \"{}\" is null, which TOML cannot store", key)),
+                None => toml::to_string(self).map_err(|e| e.to_string()),
+            },
+            ListFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+        };
+        match content {
+            Ok(content) => Ok(content),
+            Err(e) => Err(ExitCode::FailedToSerializeAs(format, e)),
+        }
+    }
     fn get_highest_num(&self) -> usize {
         let highest_num = self.items.len() + 1;
         for item in self.items.iter() {
@@ -44,63 +167,611 @@ impl List {
     }
     fn get_spacing_count(&self) -> usize {
         let highest_num = self.get_highest_num();
-        return highest_num.to_string().len();
+        highest_num.to_string().len()
     }
-    pub fn print(
-        &mut self, output: &mut String, print_which: &PrintWhich, plain: bool,
-        max_level: Option<usize>, display_hidden: bool,
-    ) -> Result<(), IOError> {
+    fn print_header(&self, output: &mut String, plain: bool) {
         let created = format!("{}", self.created.format("%m/%d/%Y %H:%M:%S"));
         let updated = format!("{}", self.last_updated.format("%m/%d/%Y %H:%M:%S"));
         if !plain {
             output.push_str(&styler::primary(styler::bold("Created On: ")));
-            output.push_str(&styler::info(styler::italic(format!("{}", created))));
+            output.push_str(&styler::info(styler::italic(&created)));
             output.push_str(&styler::primary(styler::bold("\nLast Edit : ")));
-            output.push_str(&styler::info(styler::italic(format!("{}", updated))));
+            output.push_str(&styler::info(styler::italic(&updated)));
         } else {
             output.push_str(&format!("Created On: {}", created));
             output.push_str(&format!("\nLast Edit : {}", updated));
         }
-        let mut level = 0;
-        let mut index = 1;
-        if self.items.len().eq(&0) {
-            output.push_str("\n There are no items in this list");
+    }
+    pub fn get_item(&self, path: &[usize]) -> Option<&Item> {
+        let mut items = &self.items;
+        let mut found = None;
+        for index in path.iter() {
+            if index.eq(&0) {
+                return None;
+            }
+            let item = items.get(index - 1)?;
+            items = &item.sub_items;
+            found = Some(item);
+        }
+        found
+    }
+    pub fn get_item_mut(&mut self, path: &[usize]) -> Option<&mut Item> {
+        if path.contains(&0) {
+            return None;
+        }
+        let (last, parents) = path.split_last()?;
+        let mut items = &mut self.items;
+        for index in parents.iter() {
+            items = &mut items.get_mut(index - 1)?.sub_items;
+        }
+        items.get_mut(last - 1)
+    }
+    pub fn stamp_path(&mut self, path: &[usize], time: DateTime<Local>) {
+        self.last_updated = time;
+        for depth in 1..(path.len() + 1) {
+            if let Some(item) = self.get_item_mut(&path[..depth]) {
+                item.last_updated = time;
+            }
+        }
+    }
+    pub fn parse_filter(&self, input: &str) -> Result<Filter, ParseFilterError> {
+        Filter::parse_in(input, &self.filter_context())
+    }
+    pub fn filter_context(&self) -> FilterContext {
+        FilterContext::new(self.statuses.clone(), current_user())
+    }
+    fn print_context(&self, plain: bool) -> PrintContext {
+        PrintContext {
+            plain,
+            spacing: self.get_spacing_count(),
+            blocked: self.blocked_ids(),
+            filter: self.filter_context(),
+        }
+    }
+    pub fn validate_status(&self, status: &ItemStatus) -> Result<(), ExitCode> {
+        if status.is_builtin() || self.filter_context().definition(status).is_some() {
             return Ok(());
         }
-        let spacing = self.get_spacing_count();
-        for item in self.items.iter() {
-            item.printable(
-                output, &mut index, &mut level, print_which, plain, spacing,
-                max_level, false, display_hidden,
-            )?;
-            index = index.add(1);
+        Err(ExitCode::UnknownStatus(status.to_string()))
+    }
+    fn validate_item_statuses(&self, items: &[Item]) -> Result<(), ExitCode> {
+        for item in items.iter() {
+            self.validate_status(&item.status)?;
+            self.validate_item_statuses(&item.sub_items)?;
+            self.validate_item_statuses(&item.occurrences)?;
         }
         Ok(())
     }
-    pub fn status(&mut self, content: &mut String, print_which: &PrintWhich) {
-        match print_which {
-            PrintWhich::All => {
-                content.push_str(&format!("Items: {}", self.items.len()));
+    pub fn validate_statuses(&self) -> Result<(), ExitCode> {
+        for status in self.status_cycle.iter() {
+            self.validate_status(status)?;
+        }
+        self.validate_item_statuses(&self.items)
+    }
+    fn collect_custom_statuses(items: &[Item], statuses: &mut Vec<String>) {
+        for item in items.iter() {
+            if let ItemStatus::Custom(name) = &item.status {
+                if !statuses.contains(name) {
+                    statuses.push(name.clone());
+                }
+            }
+            Self::collect_custom_statuses(&item.sub_items, statuses);
+            Self::collect_custom_statuses(&item.occurrences, statuses);
+        }
+    }
+    pub fn define_missing_statuses(&mut self) {
+        let mut names = self.status_cycle.iter()
+            .filter_map(|status| match status {
+                ItemStatus::Custom(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
+        Self::collect_custom_statuses(&self.items, &mut names);
+        for name in names.into_iter() {
+            if self.statuses.iter().all(|definition| definition.name.ne(&name)) {
+                let symbol = name.chars().next().unwrap_or('?').to_string();
+                self.statuses.push(StatusDefinition::new(&name, symbol, None, false));
+            }
+        }
+    }
+    pub fn validate_action(
+        &self, indices: &[usize], action: &ItemAction,
+    ) -> Result<(), ExitCode> {
+        match action {
+            ItemAction::AlterStatus(status) => self.validate_status(status),
+            ItemAction::CycleStatus => {
+                for status in self.status_cycle.iter() {
+                    self.validate_status(status)?;
+                }
+                Ok(())
+            },
+            ItemAction::CycleStatusIn(cycle) => {
+                for status in cycle.iter() {
+                    self.validate_status(status)?;
+                }
+                Ok(())
+            },
+            ItemAction::AddDependency(prerequisite_id) => {
+                let path = indices.iter().rev().copied().collect::<Vec<usize>>();
+                let item_id = match self.get_item(&path) {
+                    Some(item) => item.id.clone(),
+                    None => return Err(ExitCode::ItemNotFound(get_printable_coords(&path))),
+                };
+                if self.get_path_by_id(prerequisite_id).is_none() {
+                    return Err(ExitCode::ItemNotFound(prerequisite_id.clone()));
+                }
+                if self.depends_on(prerequisite_id, &item_id) {
+                    return Err(ExitCode::DependencyCycle(prerequisite_id.clone()));
+                }
+                Ok(())
             },
-            _ => {},
+            _ => Ok(()),
         }
-        if self.items.len().eq(&0) {
+    }
+    fn assign_unique_ids(item: &mut Item, seen: &mut HashSet<String>) -> bool {
+        let mut changed = false;
+        if item.id.is_empty() || seen.contains(&item.id) {
+            item.id = generate_id();
+            changed = true;
+        }
+        seen.insert(item.id.clone());
+        for sub in item.sub_items.iter_mut().chain(item.occurrences.iter_mut()) {
+            changed |= Self::assign_unique_ids(sub, seen);
+        }
+        changed
+    }
+    fn contains_null(value: &Value) -> bool {
+        match value {
+            Value::Null => true,
+            Value::Array(values) => values.iter().any(Self::contains_null),
+            Value::Object(values) => values.values().any(Self::contains_null),
+            _ => false,
+        }
+    }
+    fn find_null_field(items: &[Item]) -> Option<String> {
+        items.iter().find_map(|item| {
+            item.fields.iter()
+                .find(|(_, value)| Self::contains_null(value))
+                .map(|(key, _)| key.clone())
+                .or_else(|| Self::find_null_field(&item.sub_items))
+                .or_else(|| Self::find_null_field(&item.occurrences))
+        })
+    }
+    fn collect_ids(items: &[Item], ids: &mut HashSet<String>) {
+        for item in items.iter() {
+            ids.insert(item.id.clone());
+            Self::collect_ids(&item.sub_items, ids);
+            Self::collect_ids(&item.occurrences, ids);
+        }
+    }
+    pub fn ensure_ids(&mut self) -> bool {
+        let mut seen = HashSet::new();
+        let mut changed = false;
+        for item in self.items.iter_mut() {
+            changed |= Self::assign_unique_ids(item, &mut seen);
+        }
+        changed
+    }
+    pub fn with_unique_ids(&self, mut item: Item) -> Item {
+        let mut seen = HashSet::new();
+        Self::collect_ids(&self.items, &mut seen);
+        Self::assign_unique_ids(&mut item, &mut seen);
+        item
+    }
+    fn find_path_by_id(
+        items: &[Item], id: &str, path: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        for (i, item) in items.iter().enumerate() {
+            path.push(i + 1);
+            if item.id.eq(id) {
+                return Some(path.clone());
+            }
+            if let Some(found) = Self::find_path_by_id(&item.sub_items, id, path) {
+                return Some(found);
+            }
+            path.pop();
+        }
+        None
+    }
+    pub fn get_path_by_id(&self, id: &str) -> Option<Vec<usize>> {
+        Self::find_path_by_id(&self.items, id, &mut Vec::new())
+    }
+    pub fn resolve_id(&self, item_ref: &ItemRef) -> Result<String, ExitCode> {
+        let found = match item_ref {
+            ItemRef::Id(id) => self.get_path_by_id(id).map(|_| id.clone()),
+            ItemRef::Path(path) => self.get_item(path).map(|item| item.id.clone()),
+        };
+        match found {
+            Some(id) => Ok(id),
+            None => Err(ExitCode::ItemNotFound(item_ref.to_string())),
+        }
+    }
+    fn collect_items<'a>(items: &'a [Item], by_id: &mut HashMap<String, &'a Item>) {
+        for item in items.iter() {
+            by_id.insert(item.id.clone(), item);
+            Self::collect_items(&item.sub_items, by_id);
+        }
+    }
+    fn items_by_id(&self) -> HashMap<String, &Item> {
+        let mut by_id = HashMap::new();
+        Self::collect_items(&self.items, &mut by_id);
+        by_id
+    }
+    fn depends_on(&self, from_id: &str, target_id: &str) -> bool {
+        let by_id = self.items_by_id();
+        let mut visited = HashSet::new();
+        let mut pending = vec![from_id.to_string()];
+        while let Some(id) = pending.pop() {
+            if id.eq(target_id) {
+                return true;
+            }
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            if let Some(item) = by_id.get(&id) {
+                pending.extend(item.depends_on.iter().cloned());
+            }
+        }
+        false
+    }
+    fn act_on_id(&mut self, id: &str, action: ItemAction) -> Result<(), ExitCode> {
+        let path = match self.get_path_by_id(id) {
+            Some(path) => path,
+            None => return Err(ExitCode::ItemNotFound(id.to_string())),
+        };
+        let mut indices = path.into_iter().rev().collect::<Vec<usize>>();
+        self.try_act_on_item_at(&mut indices, action)?;
+        Ok(())
+    }
+    pub fn add_dependency(
+        &mut self, item: &ItemRef, prerequisite: &ItemRef,
+    ) -> Result<(), ExitCode> {
+        let item_id = self.resolve_id(item)?;
+        let prerequisite_id = self.resolve_id(prerequisite)?;
+        self.act_on_id(&item_id, ItemAction::AddDependency(prerequisite_id))
+    }
+    pub fn remove_dependency(
+        &mut self, item: &ItemRef, prerequisite: &ItemRef,
+    ) -> Result<(), ExitCode> {
+        let item_id = self.resolve_id(item)?;
+        let prerequisite_id = self.resolve_id(prerequisite)?;
+        self.act_on_id(&item_id, ItemAction::RemoveDependency(prerequisite_id))
+    }
+    pub fn blocked_ids(&self) -> HashSet<String> {
+        let context = self.filter_context();
+        let by_id = self.items_by_id();
+        by_id.values().filter(|item| {
+            item.depends_on.iter().any(|id| {
+                match by_id.get(id) {
+                    Some(prerequisite) => {
+                        prerequisite.item_type.is_actionable()
+                            && !context.is_done(&prerequisite.status)
+                    },
+                    None => false,
+                }
+            })
+        }).map(|item| item.id.clone()).collect()
+    }
+    fn collect_actionable(
+        items: &[Item], blocked: &HashSet<String>, context: &FilterContext,
+        path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            if item.hidden {
+                continue;
+            }
+            path.push(i + 1);
+            let is_leaf = !item.sub_items.iter().any(|sub| {
+                sub.item_type.is_actionable()
+            });
+            if is_leaf
+                && item.item_type.is_actionable()
+                && context.is_open(&item.status)
+                && !matches!(item.status, ItemStatus::Blocked | ItemStatus::Waiting)
+                && !blocked.contains(&item.id)
+            {
+                paths.push(path.clone());
+            }
+            Self::collect_actionable(&item.sub_items, blocked, context, path, paths);
+            path.pop();
+        }
+    }
+    pub fn next_actionable(&self) -> Vec<Vec<usize>> {
+        let blocked = self.blocked_ids();
+        let context = self.filter_context();
+        let mut paths = Vec::new();
+        Self::collect_actionable(
+            &self.items, &blocked, &context, &mut Vec::new(), &mut paths,
+        );
+        paths
+    }
+    pub fn print_next_actionable(&self, output: &mut String, plain: bool) {
+        let matches = self.next_actionable().into_iter().filter_map(|path| {
+            self.get_item(&path).map(|item| SearchMatch {
+                text: item.text.clone(),
+                path,
+            })
+        }).collect::<Vec<SearchMatch>>();
+        self.print_matches(output, &matches, plain);
+    }
+    pub fn search(
+        &self, query: impl AsRef<str>, mode: &SearchMode,
+    ) -> Result<Vec<SearchMatch>, ExitCode> {
+        let search = Search::new(query, mode)?;
+        let mut matches = Vec::new();
+        search.collect(&self.items, &mut Vec::new(), &mut matches);
+        Ok(matches)
+    }
+    pub fn print_matches(
+        &self, output: &mut String, matches: &[SearchMatch], plain: bool,
+    ) {
+        self.print_header(output, plain);
+        if matches.is_empty() {
+            output.push_str("\n There are no matching items in this list");
             return;
         }
-        let mut complete = 0;
-        let mut incomplete = 0;
-        for item in self.items.iter() {
-            match print_which {
-                PrintWhich::All => {
-                    complete = complete + item.count_complete();
-                    incomplete = incomplete + item.count_incomplete();
+        let context = self.print_context(plain);
+        let mut printed = HashSet::new();
+        for search_match in matches.iter() {
+            if self.is_hidden_at(&search_match.path) {
+                continue;
+            }
+            for depth in 1..(search_match.path.len() + 1) {
+                let path = &search_match.path[..depth];
+                if !printed.insert(path.to_vec()) {
+                    continue;
+                }
+                if let Some(item) = self.get_item(path) {
+                    item.print_line(output, path[depth - 1], depth - 1, &context);
+                }
+            }
+        }
+    }
+    fn is_hidden_at(&self, path: &[usize]) -> bool {
+        (1..(path.len() + 1)).any(|depth| {
+            self.get_item(&path[..depth]).is_some_and(|item| item.hidden)
+        })
+    }
+    fn collect_paths(
+        items: &[Item], filter: &Filter, context: &FilterContext,
+        path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            path.push(i + 1);
+            if filter.matches_in(item, context) {
+                paths.push(path.clone());
+            }
+            Self::collect_paths(&item.sub_items, filter, context, path, paths);
+            path.pop();
+        }
+    }
+    pub fn filter_paths(&self, filter: &Filter) -> Vec<Vec<usize>> {
+        let context = self.filter_context();
+        let mut paths = Vec::new();
+        Self::collect_paths(
+            &self.items, filter, &context, &mut Vec::new(), &mut paths,
+        );
+        paths
+    }
+    pub fn act_on_matching(
+        &mut self, filter: &Filter, action: ItemAction,
+    ) -> Result<Vec<Item>, ExitCode> {
+        let mut matched = Vec::new();
+        for path in self.filter_paths(filter).into_iter().rev() {
+            let indices = path.into_iter().rev().collect::<Vec<usize>>();
+            self.resolve_action(&indices, action.clone())?;
+            matched.push(indices);
+        }
+        let items = self.items.clone();
+        let last_updated = self.last_updated;
+        let mut out_items = Vec::new();
+        for mut indices in matched.into_iter() {
+            match self.try_act_on_item_at(&mut indices, action.clone()) {
+                Ok(Some(item)) => out_items.push(item),
+                Ok(None) => {},
+                Err(e) => {
+                    self.items = items;
+                    self.last_updated = last_updated;
+                    return Err(e);
+                },
+            }
+        }
+        Ok(out_items)
+    }
+    fn collect_archivable(
+        items: &[Item], context: &FilterContext, cutoff: Option<DateTime<Local>>,
+        path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            path.push(i + 1);
+            let done = context.is_done(&item.status);
+            match cutoff {
+                None if done => paths.push(path.clone()),
+                None => {},
+                Some(cutoff) if done && item.last_updated < cutoff => {
+                    paths.push(path.clone());
                 },
-                PrintWhich::Complete => {
-                    complete = complete + item.count_complete();
+                Some(_) => {
+                    Self::collect_archivable(
+                        &item.sub_items, context, cutoff, path, paths,
+                    );
+                },
+            }
+            path.pop();
+        }
+    }
+    pub fn take_archivable(&mut self, older_than_days: Option<u32>) -> Vec<Item> {
+        let context = self.filter_context();
+        let cutoff = older_than_days.map(|days| {
+            Local::now() - Duration::days(days as i64)
+        });
+        let mut paths = Vec::new();
+        Self::collect_archivable(
+            &self.items, &context, cutoff, &mut Vec::new(), &mut paths,
+        );
+        let mut archived = Vec::new();
+        for path in paths.into_iter().rev() {
+            let parent = path.split_last()
+                .and_then(|(_, parent)| self.get_item(parent))
+                .map(|parent| parent.id.clone());
+            let mut indices = path.iter().rev().copied().collect::<Vec<usize>>();
+            if let Some(mut item) = self.act_on_item_at(&mut indices, ItemAction::Remove) {
+                item.archived_from = Some(path);
+                item.archived_parent = parent;
+                archived.push(item);
+            }
+        }
+        archived.reverse();
+        Self::take_occurrences(
+            &mut self.items, None, cutoff, &mut Vec::new(), &mut archived,
+        );
+        archived
+    }
+    fn take_occurrences(
+        items: &mut [Item], parent: Option<&str>, cutoff: Option<DateTime<Local>>,
+        path: &mut Vec<usize>, archived: &mut Vec<Item>,
+    ) {
+        for (i, item) in items.iter_mut().enumerate() {
+            path.push(i + 1);
+            let (taken, kept) = item.occurrences.drain(..)
+                .partition::<Vec<Item>, _>(|occurrence| match cutoff {
+                    Some(cutoff) => occurrence.last_updated < cutoff,
+                    None => true,
+                });
+            item.occurrences = kept;
+            for mut occurrence in taken.into_iter() {
+                occurrence.archived_from = Some(path.clone());
+                occurrence.archived_parent = parent.map(|parent| parent.to_string());
+                archived.push(occurrence);
+            }
+            let id = item.id.clone();
+            Self::take_occurrences(&mut item.sub_items, Some(&id), cutoff, path, archived);
+            path.pop();
+        }
+    }
+    pub fn restore_items(&mut self, mut items: Vec<Item>) {
+        items.sort_by(|a, b| a.archived_from.cmp(&b.archived_from));
+        for mut item in items.into_iter() {
+            let path = item.archived_from.take().unwrap_or_default();
+            let index = path.last().copied().unwrap_or(usize::MAX);
+            let parent = match item.archived_parent.take() {
+                Some(id) => self.get_path_by_id(&id),
+                None => path.split_last().map(|(_, parent)| parent.to_vec()),
+            };
+            let siblings = match parent {
+                Some(parent) if parent.is_empty() => Some(&mut self.items),
+                Some(parent) => {
+                    self.get_item_mut(&parent).map(|parent| &mut parent.sub_items)
                 },
-                PrintWhich::Incomplete => {
-                    incomplete = incomplete + item.count_incomplete();
+                None => None,
+            };
+            match siblings {
+                Some(siblings) => {
+                    let position = index.saturating_sub(1).min(siblings.len());
+                    siblings.insert(position, item);
                 },
+                None => self.items.push(item),
+            }
+        }
+        self.last_updated = Local::now();
+    }
+    pub fn print(
+        &mut self, output: &mut String, print_which: &PrintWhich, plain: bool,
+        max_level: Option<usize>, display_hidden: bool,
+    ) -> Result<(), IOError> {
+        self.print_with(output, &PrintOptions::new(
+            Filter::from(print_which), plain, max_level, display_hidden,
+        ))
+    }
+    pub fn print_filtered(
+        &mut self, output: &mut String, filter: &Filter, plain: bool,
+        max_level: Option<usize>, display_hidden: bool,
+    ) -> Result<(), IOError> {
+        self.print_with(output, &PrintOptions::new(
+            filter.clone(), plain, max_level, display_hidden,
+        ))
+    }
+    pub fn print_with(
+        &mut self, output: &mut String, options: &PrintOptions,
+    ) -> Result<(), IOError> {
+        self.print_header(output, options.plain);
+        if self.items.len().eq(&0) {
+            output.push_str("\n There are no items in this list");
+            return Ok(());
+        }
+        let context = self.print_context(options.plain);
+        let order = match &options.sort {
+            Some(sort) => sort.order(&self.items),
+            None => (0..self.items.len()).collect(),
+        };
+        for i in order {
+            self.items[i].print_tree(output, i + 1, 0, false, options, &context)?;
+        }
+        Ok(())
+    }
+    pub fn sort(&mut self, options: &SortOptions) {
+        options.sort(&mut self.items);
+        self.last_updated = Local::now();
+    }
+    fn count_assignees(
+        items: &[Item], filter: &Filter, context: &FilterContext,
+        counts: &mut BTreeMap<String, usize>,
+    ) {
+        for item in items.iter() {
+            if filter.matches_in(item, context) && item.item_type.is_actionable() {
+                for assignee in item.assignees.iter() {
+                    *counts.entry(assignee.clone()).or_insert(0) += 1;
+                }
+            }
+            Self::count_assignees(&item.sub_items, filter, context, counts);
+        }
+    }
+    fn count_statuses(&self, filter: &Filter) -> (usize, Vec<(ItemStatus, usize)>) {
+        let mut matching = 0;
+        let mut counts: Vec<(ItemStatus, usize)> = Vec::new();
+        for path in self.filter_paths(filter).iter() {
+            let item = match self.get_item(path) {
+                Some(item) => item,
+                None => continue,
+            };
+            matching += 1;
+            if !item.item_type.is_actionable() {
+                continue;
+            }
+            match counts.iter_mut().find(|(status, _)| status.eq(&item.status)) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((item.status.clone(), 1)),
+            }
+        }
+        (matching, counts)
+    }
+    pub fn status(&mut self, content: &mut String, print_which: &PrintWhich) {
+        if let PrintWhich::All = print_which {
+            content.push_str(&format!("Items: {}", self.items.len()));
+        }
+        if self.items.len().eq(&0) {
+            return;
+        }
+        let context = self.filter_context();
+        let filter = Filter::from(print_which);
+        let (_, counts) = self.count_statuses(&filter);
+        let count_of = |status: &ItemStatus| {
+            counts.iter().find(|(other, _)| other.eq(status)).map_or(0, |(_, count)| *count)
+        };
+        let complete = counts.iter()
+            .filter(|(status, _)| context.is_done(status))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+        let incomplete = count_of(&ItemStatus::Incomplete);
+        let mut other_statuses = vec![
+            (ItemStatus::InProgress, "In Progress".to_string()),
+            (ItemStatus::Blocked, "Blocked".to_string()),
+            (ItemStatus::Waiting, "Waiting".to_string()),
+        ];
+        for definition in self.statuses.iter() {
+            let status = ItemStatus::Custom(definition.name.clone());
+            if !other_statuses.iter().any(|(other, _)| other.eq(&status)) {
+                other_statuses.push((status, definition.name.clone()));
             }
         }
         match print_which {
@@ -115,5 +786,368 @@ impl List {
                 content.push_str(&format!("\nIncomplete: {}", incomplete));
             },
         }
+        for (status, name) in other_statuses.iter() {
+            let count = count_of(status);
+            if count.ne(&0) && context.is_open(status) {
+                content.push_str(&format!("\n{}: {}", name, count));
+            }
+        }
+        let mut assignees = BTreeMap::new();
+        Self::count_assignees(&self.items, &filter, &context, &mut assignees);
+        for (assignee, count) in assignees.iter() {
+            content.push_str(&format!("\n@{}: {}", assignee, count));
+        }
+        let mut effort = EffortTotals::default();
+        for item in self.items.iter() {
+            effort.add(&item.effort(&context));
+        }
+        if effort.is_zero() {
+            return;
+        }
+        match print_which {
+            PrintWhich::All => {
+                content.push_str(&format!("\nEffort Remaining: {}", effort.remaining));
+                content.push_str(&format!("\nEffort Completed: {}", effort.completed));
+            },
+            PrintWhich::Complete => {
+                content.push_str(&format!("\nEffort Completed: {}", effort.completed));
+            },
+            PrintWhich::Incomplete => {
+                content.push_str(&format!("\nEffort Remaining: {}", effort.remaining));
+            },
+        }
+    }
+    pub fn status_filtered(&mut self, content: &mut String, filter: &Filter) {
+        let context = self.filter_context();
+        let (matching, counts) = self.count_statuses(filter);
+        let mut complete = 0;
+        let mut incomplete = 0;
+        for (status, count) in counts.iter() {
+            if context.is_done(status) {
+                complete += count;
+            } else if context.is_open(status) {
+                incomplete += count;
+            }
+        }
+        content.push_str(&format!("Matching: {}", matching));
+        content.push_str(&format!("\nComplete: {}", complete));
+        content.push_str(&format!("\nIncomplete: {}", incomplete));
+    }
+    fn collect_time(
+        items: &[Item], path: &mut Vec<usize>,
+        per_item: &mut Vec<(Vec<usize>, String, Duration)>,
+        per_day: &mut BTreeMap<NaiveDate, Duration>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            path.push(i + 1);
+            let total = item.total_logged_time();
+            if !total.is_zero() {
+                per_item.push((path.clone(), item.text.clone(), total));
+            }
+            Self::collect_days(item, false, per_day);
+            Self::collect_time(&item.sub_items, path, per_item, per_day);
+            path.pop();
+        }
+    }
+    fn collect_days(
+        item: &Item, recursive: bool, per_day: &mut BTreeMap<NaiveDate, Duration>,
+    ) {
+        for entry in item.time_entries.iter() {
+            let day = per_day.entry(entry.start.date_naive())
+                .or_insert_with(Duration::zero);
+            *day += entry.duration();
+        }
+        for occurrence in item.occurrences.iter() {
+            Self::collect_days(occurrence, true, per_day);
+        }
+        if recursive {
+            for sub in item.sub_items.iter() {
+                Self::collect_days(sub, true, per_day);
+            }
+        }
+    }
+    pub fn time_report(&self, output: &mut String, plain: bool) {
+        let mut per_item = Vec::new();
+        let mut per_day = BTreeMap::new();
+        Self::collect_time(&self.items, &mut Vec::new(), &mut per_item, &mut per_day);
+        if per_item.is_empty() {
+            output.push_str("There is no logged time in this list");
+            return;
+        }
+        if !plain {
+            output.push_str(&styler::primary(styler::bold("Time Per Item")));
+        } else {
+            output.push_str("Time Per Item");
+        }
+        for (path, text, total) in per_item.iter() {
+            let coords = get_printable_coords(path);
+            let duration = format_duration(total);
+            if !plain {
+                output.push_str(&styler::bold(format!("\n{}. ", coords)));
+                output.push_str(&format!("{} ", text));
+                output.push_str(&styler::info(duration));
+            } else {
+                output.push_str(&format!("\n{}. {} {}", coords, text, duration));
+            }
+        }
+        if !plain {
+            output.push_str(&styler::primary(styler::bold("\nTime Per Day")));
+        } else {
+            output.push_str("\nTime Per Day");
+        }
+        let mut total = Duration::zero();
+        for (day, duration) in per_day.iter() {
+            total += *duration;
+            let day = format!("{}", day.format("%m/%d/%Y"));
+            let duration = format_duration(duration);
+            if !plain {
+                output.push_str(&styler::bold(format!("\n{}: ", day)));
+                output.push_str(&styler::info(duration));
+            } else {
+                output.push_str(&format!("\n{}: {}", day, duration));
+            }
+        }
+        if !plain {
+            output.push_str(&styler::primary(styler::bold("\nTotal: ")));
+            output.push_str(&styler::info(format_duration(&total)));
+        } else {
+            output.push_str(&format!("\nTotal: {}", format_duration(&total)));
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ enums::ItemType, test_utils::todo, },
+    };
+    fn sample() -> List {
+        let mut list = List::new("sample".to_string());
+        list.statuses.push(StatusDefinition::new("shipped", "s", None, true));
+        list.statuses.push(StatusDefinition::new("review", "r", None, false));
+        let mut parent = todo("release", ItemStatus::Incomplete);
+        parent.sub_items.push(todo("build", ItemStatus::Complete));
+        parent.sub_items.push(todo("tag", ItemStatus::Custom("shipped".to_string())));
+        parent.sub_items.push(todo("notes", ItemStatus::Custom("review".to_string())));
+        list.items.push(parent);
+        list.items.push(todo("blog", ItemStatus::InProgress));
+        list.items.push(Item::new(ItemType::Note, "remember"));
+        list
+    }
+    fn status_of(list: &mut List, print_which: PrintWhich) -> String {
+        let mut content = String::new();
+        list.status(&mut content, &print_which);
+        content
+    }
+    #[test]
+    fn status_counts_nested_items_once() {
+        let content = status_of(&mut sample(), PrintWhich::All);
+        assert_eq!(
+            content,
+            "Items: 3\nComplete: 2\nIncomplete: 1\nIn Progress: 1\nreview: 1",
+        );
+    }
+    #[test]
+    fn status_views_follow_print_which_filters() {
+        assert_eq!(status_of(&mut sample(), PrintWhich::Complete), "\nComplete: 2");
+        assert_eq!(
+            status_of(&mut sample(), PrintWhich::Incomplete),
+            "\nIncomplete: 1\nIn Progress: 1\nreview: 1",
+        );
+    }
+    #[test]
+    fn status_filtered_counts_matching_items() {
+        let mut content = String::new();
+        let filter = "not status:incomplete".parse::<Filter>().unwrap();
+        sample().status_filtered(&mut content, &filter);
+        assert_eq!(content, "Matching: 4\nComplete: 2\nIncomplete: 2");
+    }
+    #[test]
+    fn completing_a_recurring_item_keeps_history_out_of_the_tree() {
+        let mut list = List::new("recurring".to_string());
+        let mut item = todo("water plants", ItemStatus::Incomplete);
+        item.recurrence = Some(crate::enums::Recurrence::Daily);
+        let id = item.id.clone();
+        list.items.push(item);
+        list.items.push(todo("after", ItemStatus::Incomplete));
+        list.act_on_item_at(&mut vec![1], ItemAction::AlterStatus(ItemStatus::Complete));
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[0].id, id);
+        assert!(list.items[0].status.eq(&ItemStatus::Incomplete));
+        assert!(list.items[0].due.is_some());
+        assert_eq!(list.items[0].occurrences.len(), 1);
+        assert!(list.items[0].occurrences[0].id.ne(&id));
+        assert_eq!(list.items[1].text, "after");
+        assert_eq!(
+            status_of(&mut list, PrintWhich::All),
+            "Items: 2\nComplete: 0\nIncomplete: 2",
+        );
+        let archived = list.take_archivable(None);
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].status.eq(&ItemStatus::Complete));
+        assert_eq!(archived[0].archived_from, Some(vec![1]));
+        assert!(list.items[0].occurrences.is_empty());
+    }
+    #[test]
+    fn custom_done_statuses_roll_recurring_items_over() {
+        let mut list = sample();
+        list.status_cycle = vec![ItemStatus::Incomplete, ItemStatus::Custom("shipped".to_string())];
+        let mut nested = todo("invoice", ItemStatus::Incomplete);
+        nested.recurrence = Some(crate::enums::Recurrence::Daily);
+        list.items[0].sub_items.push(nested);
+        list.items[1].recurrence = Some(crate::enums::Recurrence::Daily);
+        let shipped = ItemAction::AlterStatus(ItemStatus::Custom("shipped".to_string()));
+        list.try_act_on_item_at(&mut vec![2], shipped).unwrap();
+        assert!(list.items[1].status.eq(&ItemStatus::Incomplete));
+        assert_eq!(list.items[1].occurrences.len(), 1);
+        assert!(list.items[1].occurrences[0].status.eq(&ItemStatus::Custom("shipped".to_string())));
+        list.try_act_on_item_at(&mut vec![4, 1], ItemAction::CycleStatus).unwrap();
+        let invoice = &list.items[0].sub_items[3];
+        assert!(invoice.status.eq(&ItemStatus::Incomplete));
+        assert_eq!(invoice.occurrences.len(), 1);
+        list.try_act_on_item_at(&mut vec![2], ItemAction::AlterStatus(ItemStatus::Blocked))
+            .unwrap();
+        assert_eq!(list.items[1].occurrences.len(), 1);
+    }
+    #[test]
+    fn ensure_ids_fills_missing_and_duplicate_ids() {
+        let mut list = sample();
+        list.items[0].id.clear();
+        list.items[1].id = list.items[0].sub_items[0].id.clone();
+        assert!(list.ensure_ids());
+        let mut ids = HashSet::new();
+        List::collect_ids(&list.items, &mut ids);
+        assert_eq!(ids.len(), 6);
+        assert!(!ids.contains(""));
+        assert!(!list.ensure_ids());
+    }
+    #[test]
+    fn put_reassigns_colliding_ids() {
+        let mut list = sample();
+        let copy = list.items[0].clone();
+        list.act_on_item_at(&mut Vec::new(), ItemAction::Put(Box::new(copy)));
+        assert_eq!(list.items.len(), 4);
+        assert!(list.items[3].id.ne(&list.items[0].id));
+        assert!(list.items[3].sub_items[0].id.ne(&list.items[0].sub_items[0].id));
+        assert!(!list.ensure_ids());
+    }
+    #[test]
+    fn add_dependency_action_rejects_cycles_and_unknown_ids() {
+        let mut list = sample();
+        let first = list.items[0].id.clone();
+        let second = list.items[1].id.clone();
+        list.try_act_on_item_at(&mut vec![1], ItemAction::AddDependency(second.clone()))
+            .unwrap();
+        assert_eq!(list.items[0].depends_on, vec![second.clone()]);
+        assert!(matches!(
+            list.try_act_on_item_at(&mut vec![2], ItemAction::AddDependency(first.clone())),
+            Err(ExitCode::DependencyCycle(_)),
+        ));
+        assert!(matches!(
+            list.try_act_on_item_at(&mut vec![2], ItemAction::AddDependency(second.clone())),
+            Err(ExitCode::DependencyCycle(_)),
+        ));
+        let missing = ItemAction::AddDependency("missing".to_string());
+        assert!(matches!(
+            list.try_act_on_item_at(&mut vec![2], missing),
+            Err(ExitCode::ItemNotFound(_)),
+        ));
+        assert!(matches!(
+            list.try_act_on_item_at(&mut vec![9], ItemAction::AddDependency(first.clone())),
+            Err(ExitCode::ItemNotFound(_)),
+        ));
+        assert!(list.items[1].depends_on.is_empty());
+        list.act_on_item_at(&mut vec![2], ItemAction::AddDependency(first));
+        assert_eq!(list.items[1].depends_on.len(), 1);
+    }
+    #[test]
+    fn unknown_statuses_are_reported() {
+        let mut list = sample();
+        let unknown = ItemAction::AlterStatus(ItemStatus::Custom("bogus".to_string()));
+        assert!(matches!(
+            list.try_act_on_item_at(&mut vec![2], unknown.clone()),
+            Err(ExitCode::UnknownStatus(_)),
+        ));
+        assert!(matches!(
+            list.act_on_matching(&Filter::All, unknown),
+            Err(ExitCode::UnknownStatus(_)),
+        ));
+        let shipped = ItemAction::AlterStatus(ItemStatus::Custom("shipped".to_string()));
+        assert!(list.try_act_on_item_at(&mut vec![2], shipped).is_ok());
+        let bogus = ItemStatus::Custom("bogus".to_string());
+        list.act_on_item_at(&mut vec![3], ItemAction::AlterStatus(bogus.clone()));
+        assert!(list.items[2].status.eq(&bogus));
+    }
+    #[test]
+    fn bulk_actions_apply_to_every_match_or_none() {
+        let mut list = sample();
+        let first = list.items[0].id.clone();
+        let before = list.to_json().unwrap();
+        assert!(matches!(
+            list.act_on_matching(&Filter::All, ItemAction::AddDependency(first)),
+            Err(ExitCode::DependencyCycle(_)),
+        ));
+        assert_eq!(list.to_json().unwrap(), before);
+        let tagged = list.act_on_matching(&Filter::All, ItemAction::AddTag("q3".to_string()));
+        assert!(tagged.unwrap().is_empty());
+        assert_eq!(list.filter_paths(&"tag:q3".parse().unwrap()).len(), 6);
+    }
+    #[test]
+    fn loading_defines_undefined_custom_statuses() {
+        let list = sample();
+        let json = list.to_json().unwrap();
+        assert_eq!(List::from_json(json).unwrap().statuses.len(), 2);
+        let mut undefined = list.clone();
+        undefined.statuses.clear();
+        undefined.status_cycle = vec![ItemStatus::Custom("parked".to_string())];
+        assert!(undefined.validate_statuses().is_err());
+        let loaded = List::from_json(undefined.to_json().unwrap()).unwrap();
+        assert!(loaded.validate_statuses().is_ok());
+        let names = loaded.statuses.iter().map(|d| d.name.as_str()).collect::<Vec<&str>>();
+        assert_eq!(names, vec!["parked", "shipped", "review"]);
+        assert!(!loaded.filter_context().is_done(&ItemStatus::Custom("shipped".to_string())));
+        let toml = undefined.to_format(ListFormat::Toml).unwrap();
+        assert!(List::from_format(toml, ListFormat::Toml).unwrap().validate_statuses().is_ok());
+        assert!(loaded.parse_filter("status:parked").is_ok());
+        assert!(loaded.parse_filter("status:parkd").is_err());
+    }
+    #[test]
+    fn toml_rejects_null_fields() {
+        let mut list = sample();
+        list.items[0].sub_items[0].fields.insert("owner".to_string(), Value::Null);
+        assert!(matches!(
+            list.to_format(ListFormat::Toml),
+            Err(ExitCode::FailedToSerializeAs(ListFormat::Toml, e)) if e.contains("owner"),
+        ));
+        assert!(list.to_format(ListFormat::Yaml).is_ok());
+        list.items[0].sub_items[0].fields
+            .insert("owner".to_string(), Value::from(vec![Value::from(1)]));
+        let toml = list.to_format(ListFormat::Toml).unwrap();
+        assert!(List::from_format(toml, ListFormat::Toml).is_ok());
+    }
+    #[test]
+    fn importers_define_the_custom_statuses_they_read() {
+        let csv = "path,depth,type,status,text\n1,1,todo,review,check it\n";
+        let list = List::from_table("imported", csv, TableFormat::Csv);
+        assert!(list.validate_statuses().is_ok());
+        assert_eq!(list.statuses.len(), 1);
+        assert_eq!(list.statuses[0].name, "review");
+    }
+    #[test]
+    fn milestones_count_as_actionable_items() {
+        let mut list = List::new("milestones".to_string());
+        let mut milestone = Item::new(ItemType::Milestone, "beta");
+        milestone.estimate = Some(crate::enums::Estimate::Minutes(60));
+        list.items.push(milestone);
+        list.items.push(Item::new(ItemType::Heading, "later"));
+        assert_eq!(
+            status_of(&mut list, PrintWhich::All),
+            "Items: 2\nComplete: 0\nIncomplete: 1\nEffort Remaining: 1h 00m\nEffort Completed: 0",
+        );
+        assert_eq!(list.next_actionable(), vec![vec![1]]);
+        let mut output = String::new();
+        list.print(&mut output, &PrintWhich::Incomplete, true, None, false).unwrap();
+        assert!(output.contains("beta"));
+        assert!(!output.contains("later"));
     }
 }
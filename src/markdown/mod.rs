@@ -0,0 +1,139 @@
+use {
+    crate::{
+        enums::{ ItemStatus, ItemType, },
+        item::Item,
+        list::List,
+        utils::{ collapse_outline, OutlineLevel, },
+    },
+    regex::Regex,
+};
+const TAB_WIDTH: usize = 4;
+struct Patterns {
+    heading: Regex,
+    list_item: Regex,
+    strikethrough: Regex,
+    link: Regex,
+}
+impl Patterns {
+    fn new() -> Self {
+        Self {
+            heading: Regex::new(r"^\s{0,3}(#{1,6})\s+(.*?)(?:\s+#+)?\s*$").unwrap(),
+            list_item: Regex::new(r"^([ \t]*)(?:[-*+]|\d+[.)])\s+(?:\[([ xX-])\](?:\s+|$))?(.*)$").unwrap(),
+            strikethrough: Regex::new(r"^~~(.+)~~$").unwrap(),
+            link: Regex::new(r"^\[([^\]]*)\]\(([^)\s]+)\)$").unwrap(),
+        }
+    }
+}
+fn indent_width(indent: &str) -> usize {
+    indent.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+fn list_item(check: Option<&str>, text: &str, patterns: &Patterns) -> Item {
+    let (text, struck) = match patterns.strikethrough.captures(text) {
+        Some(captures) => (captures[1].trim().to_string(), true),
+        None => (text.to_string(), false),
+    };
+    let mut item = match check {
+        Some(check) => {
+            let mut item = Item::new(ItemType::Todo, "");
+            item.status = match check {
+                "x" | "X" => ItemStatus::Complete,
+                "-" => ItemStatus::InProgress,
+                _ => ItemStatus::Incomplete,
+            };
+            item
+        },
+        None => Item::new(ItemType::Note, ""),
+    };
+    match patterns.link.captures(&text) {
+        Some(captures) if check.is_none() => {
+            item.item_type = ItemType::Link;
+            item.link = Some(captures[2].to_string());
+            item.text = match &captures[1] {
+                "" => captures[2].to_string(),
+                title => title.to_string(),
+            };
+        },
+        _ => item.text = text,
+    }
+    if struck {
+        item.status = ItemStatus::Disabled;
+    }
+    item
+}
+pub fn import(name: impl AsRef<str>, content: &str) -> List {
+    let patterns = Patterns::new();
+    let mut list = List::new(name.as_ref().to_string());
+    let mut stack: Vec<(OutlineLevel, Item)> = Vec::new();
+    let mut fence: Option<String> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence.as_ref() {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            continue;
+        }
+        if let Some(captures) = patterns.heading.captures(line) {
+            let level = OutlineLevel::Heading(captures[1].len());
+            collapse_outline(&mut stack, &mut list.items, level);
+            stack.push((level, Item::new(ItemType::Heading, &captures[2])));
+            continue;
+        }
+        if let Some(captures) = patterns.list_item.captures(line) {
+            let level = OutlineLevel::ListItem(indent_width(&captures[1]));
+            collapse_outline(&mut stack, &mut list.items, level);
+            let check = captures.get(2).map(|m| m.as_str());
+            stack.push((level, list_item(check, captures[3].trim(), &patterns)));
+        }
+    }
+    collapse_outline(&mut stack, &mut list.items, OutlineLevel::Heading(0));
+    list
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_utils::texts,
+    };
+    #[test]
+    fn headings_and_lists_nest_by_level_and_indent() {
+        let content = "- loose\n\
+            # Release\n\
+            - [x] build\n\
+            \t- [ ] sign\n\
+            - ~~drop~~\n\
+            - [-] test\n\
+            ## Docs\n\
+            1. [guide](https://example.com)\n\
+            ```\n\
+            - not an item\n\
+            ```\n\
+            # Later\n";
+        let list = import("plan.md", content);
+        assert_eq!(texts(&list.items), vec!["loose", "Release", "Later"]);
+        let release = &list.items[1];
+        assert_eq!(texts(&release.sub_items), vec!["build", "drop", "test", "Docs"]);
+        assert!(release.sub_items[0].status.eq(&ItemStatus::Complete));
+        assert_eq!(texts(&release.sub_items[0].sub_items), vec!["sign"]);
+        assert!(release.sub_items[1].status.eq(&ItemStatus::Disabled));
+        assert!(release.sub_items[2].status.eq(&ItemStatus::InProgress));
+        let guide = &release.sub_items[3].sub_items[0];
+        assert!(guide.item_type.eq(&ItemType::Link));
+        assert_eq!(guide.link.as_deref(), Some("https://example.com"));
+        assert_eq!(release.sub_items[3].sub_items.len(), 1);
+    }
+    #[test]
+    fn empty_checkboxes_stay_todos() {
+        let list = import("plan.md", "- [ ]\n- [x]\n- [ ] \n");
+        assert_eq!(list.items.len(), 3);
+        assert!(list.items.iter().all(|item| item.item_type.eq(&ItemType::Todo)));
+        assert_eq!(texts(&list.items), vec!["", "", ""]);
+        assert!(list.items[1].status.eq(&ItemStatus::Complete));
+    }
+}
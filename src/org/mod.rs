@@ -0,0 +1,411 @@
+use {
+    chrono::{ DateTime, Local, NaiveDate, NaiveTime, TimeZone, },
+    crate::{
+        enums::{ ItemStatus, ItemType, },
+        item::Item,
+        list::List,
+        utils::{
+            collapse_outline, date_to_local, letter_priority, priority_letter, OutlineLevel,
+        },
+    },
+    regex::Regex,
+    serde_json::Value,
+    std::{ collections::HashSet, str::FromStr, },
+};
+fn keyword(status: &ItemStatus) -> &'static str {
+    match status {
+        ItemStatus::Complete => "DONE",
+        ItemStatus::Disabled => "CANCELLED",
+        ItemStatus::InProgress => "STARTED",
+        ItemStatus::Blocked => "BLOCKED",
+        ItemStatus::Waiting => "WAITING",
+        ItemStatus::Incomplete | ItemStatus::Custom(_) => "TODO",
+    }
+}
+fn keyword_status(keyword: &str) -> Option<ItemStatus> {
+    match keyword {
+        "TODO" => Some(ItemStatus::Incomplete),
+        "DONE" => Some(ItemStatus::Complete),
+        "CANCELLED" | "CANCELED" => Some(ItemStatus::Disabled),
+        "STARTED" => Some(ItemStatus::InProgress),
+        "BLOCKED" => Some(ItemStatus::Blocked),
+        "WAITING" => Some(ItemStatus::Waiting),
+        _ => None,
+    }
+}
+const BODY: &str = "body";
+fn is_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars()
+        .all(|c| c.is_alphanumeric() || "_@#%".contains(c))
+}
+fn is_list_item(item: &Item) -> bool {
+    item.item_type.eq(&ItemType::Note) && item.sub_items.iter().all(is_list_item)
+}
+fn title(item: &Item) -> String {
+    match (&item.item_type, &item.link) {
+        (ItemType::Link, Some(link)) => format!("[[{}][{}]]", link, item.text),
+        _ => item.text.clone(),
+    }
+}
+fn export_list_item(item: &Item, indent: usize, output: &mut String) {
+    output.push_str(&format!("{}- {}\n", " ".repeat(indent), title(item)));
+    for sub in item.sub_items.iter() {
+        export_list_item(sub, indent + 2, output);
+    }
+}
+fn export_headline(item: &Item, depth: usize, referenced: &HashSet<String>, output: &mut String) {
+    let mut headline = "*".repeat(depth);
+    let has_keyword = matches!(item.item_type, ItemType::Todo | ItemType::Milestone);
+    if has_keyword {
+        headline.push(' ');
+        headline.push_str(keyword(&item.status));
+    }
+    if let Some(priority) = item.priority {
+        headline.push_str(&format!(" [#{}]", priority_letter(priority)));
+    }
+    headline.push(' ');
+    headline.push_str(&title(item));
+    let tags = item.tags.iter()
+        .filter(|tag| is_tag(tag))
+        .cloned()
+        .collect::<Vec<String>>();
+    if !tags.is_empty() {
+        headline.push_str(&format!(" :{}:", tags.join(":")));
+    }
+    output.push_str(&headline);
+    output.push('\n');
+    let mut planning = Vec::new();
+    if has_keyword && item.status.eq(&ItemStatus::Complete) {
+        planning.push(format!("CLOSED: [{}]", item.last_updated.format("%Y-%m-%d %a %H:%M")));
+    }
+    if let Some(due) = item.due {
+        planning.push(format!("SCHEDULED: <{}>", due.format("%Y-%m-%d %a")));
+    }
+    if !planning.is_empty() {
+        output.push_str(&format!("{}\n", planning.join(" ")));
+    }
+    let mut properties = Vec::new();
+    if referenced.contains(&item.id) || !item.depends_on.is_empty() {
+        properties.push(("ID".to_string(), item.id.clone()));
+    }
+    if !item.depends_on.is_empty() {
+        properties.push(("DEPENDS".to_string(), item.depends_on.join(" ")));
+    }
+    match item.item_type {
+        ItemType::Milestone | ItemType::Note | ItemType::Link => {
+            properties.push(("TYPE".to_string(), item.item_type.to_string()));
+        },
+        _ => {},
+    }
+    if let ItemStatus::Custom(status) = &item.status {
+        properties.push(("STATUS".to_string(), status.clone()));
+    }
+    if item.hidden {
+        properties.push(("HIDDEN".to_string(), "t".to_string()));
+    }
+    let keys = item.fields.keys()
+        .filter(|key| !key.contains(char::is_whitespace) && key.as_str() != BODY);
+    for key in keys {
+        if let Some(value) = item.field_text(key) {
+            properties.push((key.clone(), value.replace('\n', " ")));
+        }
+    }
+    if !properties.is_empty() {
+        output.push_str(":PROPERTIES:\n");
+        for (key, value) in properties.iter() {
+            output.push_str(&format!(":{}: {}\n", key, value));
+        }
+        output.push_str(":END:\n");
+    }
+    if let Some(body) = item.field_text(BODY) {
+        output.push_str(&body);
+        output.push('\n');
+    }
+    for sub in item.sub_items.iter().filter(|sub| is_list_item(sub)) {
+        export_list_item(sub, 0, output);
+    }
+    for sub in item.sub_items.iter().filter(|sub| !is_list_item(sub)) {
+        export_headline(sub, depth + 1, referenced, output);
+    }
+}
+pub fn export(list: &List) -> String {
+    let mut referenced = HashSet::new();
+    collect_dependencies(&list.items, &mut referenced);
+    let mut output = String::new();
+    for item in list.items.iter().filter(|item| is_list_item(item)) {
+        export_list_item(item, 0, &mut output);
+    }
+    for item in list.items.iter().filter(|item| !is_list_item(item)) {
+        export_headline(item, 1, &referenced, &mut output);
+    }
+    output
+}
+fn collect_dependencies(items: &[Item], referenced: &mut HashSet<String>) {
+    for item in items.iter() {
+        referenced.extend(item.depends_on.iter().cloned());
+        collect_dependencies(&item.sub_items, referenced);
+    }
+}
+struct Patterns {
+    headline: Regex,
+    list_item: Regex,
+    link: Regex,
+    timestamp: Regex,
+    property: Regex,
+}
+impl Patterns {
+    fn new() -> Self {
+        Self {
+            headline: Regex::new(
+                r"^(\*+)\s+(?:([A-Z]+)\s+)?(?:\[#([A-Z])\]\s+)?(.*?)(?:\s+(:[^\s]+:))?\s*$"
+            ).unwrap(),
+            list_item: Regex::new(r"^(\s*)[-+]\s+(?:\[([ Xx-])\]\s+)?(.*)$").unwrap(),
+            link: Regex::new(r"^\[\[([^\]]+)\](?:\[([^\]]*)\])?\]$").unwrap(),
+            timestamp: Regex::new(
+                r"(CLOSED|SCHEDULED|DEADLINE):\s*[<\[](\d{4}-\d{2}-\d{2})(?:\s+[^\s\]>\d]+)?(?:\s+(\d{1,2}:\d{2}))?[^\]>]*[\]>]"
+            ).unwrap(),
+            property: Regex::new(r"^\s*:([^:\s]+):\s*(.*?)\s*$").unwrap(),
+        }
+    }
+}
+fn timestamp(date: &str, time: Option<&str>) -> Option<DateTime<Local>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    match time.and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok()) {
+        Some(time) => Local.from_local_datetime(&date.and_time(time)).earliest(),
+        None => Some(date_to_local(date)),
+    }
+}
+fn append_body(item: &mut Item, line: &str) {
+    let body = match item.field_text(BODY) {
+        Some(body) => format!("{}\n{}", body, line),
+        None if line.trim().is_empty() => return,
+        None => line.to_string(),
+    };
+    item.fields.insert(BODY.to_string(), Value::String(body));
+}
+fn trim_body(items: &mut [Item]) {
+    for item in items.iter_mut() {
+        if let Some(body) = item.field_text(BODY) {
+            item.fields.insert(BODY.to_string(), Value::String(body.trim_end().to_string()));
+        }
+        trim_body(&mut item.sub_items);
+    }
+}
+fn apply_title(item: &mut Item, title: &str, patterns: &Patterns) {
+    match patterns.link.captures(title) {
+        Some(captures) => {
+            let link = captures.get(1).map_or("", |m| m.as_str()).to_string();
+            item.text = captures.get(2).map_or(link.as_str(), |m| m.as_str()).to_string();
+            item.link = Some(link);
+            item.item_type = ItemType::Link;
+        },
+        None => item.text = title.to_string(),
+    }
+}
+pub fn import(name: impl AsRef<str>, content: &str) -> List {
+    let patterns = Patterns::new();
+    let mut list = List::new(name.as_ref().to_string());
+    let mut stack: Vec<(OutlineLevel, Item)> = Vec::new();
+    let mut in_drawer = false;
+    for line in content.lines() {
+        if let Some(captures) = patterns.headline.captures(line) {
+            in_drawer = false;
+            let level = OutlineLevel::Heading(captures[1].len());
+            collapse_outline(&mut stack, &mut list.items, level);
+            let mut item = Item::new(ItemType::Heading, "");
+            let mut title = captures.get(4).map_or("", |m| m.as_str()).to_string();
+            if let Some(word) = captures.get(2) {
+                match keyword_status(word.as_str()) {
+                    Some(status) => {
+                        item.item_type = ItemType::Todo;
+                        item.status = status;
+                    },
+                    None => title = format!("{} {}", word.as_str(), title).trim().to_string(),
+                }
+            }
+            item.priority = captures.get(3).and_then(|m| letter_priority(m.as_str()));
+            if let Some(tags) = captures.get(5) {
+                item.tags = tags.as_str().split(':')
+                    .filter(|tag| !tag.is_empty())
+                    .map(|tag| tag.to_string())
+                    .collect();
+            }
+            apply_title(&mut item, &title, &patterns);
+            stack.push((level, item));
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+            in_drawer = true;
+            continue;
+        }
+        if in_drawer {
+            if trimmed.eq_ignore_ascii_case(":END:") {
+                in_drawer = false;
+                continue;
+            }
+            let item = match stack.last_mut() {
+                Some((OutlineLevel::Heading(_), item)) => item,
+                _ => continue,
+            };
+            if let Some(captures) = patterns.property.captures(line) {
+                let value = captures[2].to_string();
+                match captures[1].to_uppercase().as_str() {
+                    "ID" => item.id = value,
+                    "DEPENDS" => {
+                        item.depends_on = value.split_whitespace()
+                            .map(|id| id.to_string())
+                            .collect();
+                    },
+                    "TYPE" => {
+                        if let Ok(item_type) = ItemType::from_str(&value) {
+                            item.item_type = item_type;
+                        }
+                    },
+                    "STATUS" => {
+                        if let Ok(status) = ItemStatus::from_str(&value) {
+                            item.status = status;
+                        }
+                    },
+                    "HIDDEN" => item.hidden = value.eq("t"),
+                    _ => {
+                        item.fields.insert(captures[1].to_string(), Value::String(value));
+                    },
+                }
+            }
+            continue;
+        }
+        if patterns.timestamp.is_match(line) {
+            let item = match stack.last_mut() {
+                Some((OutlineLevel::Heading(_), item)) => item,
+                _ => continue,
+            };
+            for captures in patterns.timestamp.captures_iter(line) {
+                let time = timestamp(&captures[2], captures.get(3).map(|m| m.as_str()));
+                match &captures[1] {
+                    "CLOSED" => {
+                        if let Some(time) = time {
+                            item.last_updated = time;
+                        }
+                    },
+                    _ => item.due = time.or(item.due),
+                }
+            }
+            continue;
+        }
+        if let Some(captures) = patterns.list_item.captures(line) {
+            let level = OutlineLevel::ListItem(captures[1].len());
+            collapse_outline(&mut stack, &mut list.items, level);
+            let mut item = match captures.get(2).map(|m| m.as_str()) {
+                Some(check) => {
+                    let mut item = Item::new(ItemType::Todo, "");
+                    item.status = match check {
+                        "X" | "x" => ItemStatus::Complete,
+                        "-" => ItemStatus::InProgress,
+                        _ => ItemStatus::Incomplete,
+                    };
+                    item
+                },
+                None => Item::new(ItemType::Note, ""),
+            };
+            let title = captures[3].trim().to_string();
+            let note = item.item_type.eq(&ItemType::Note);
+            apply_title(&mut item, &title, &patterns);
+            if !note {
+                item.item_type = ItemType::Todo;
+            }
+            stack.push((level, item));
+            continue;
+        }
+        let heading = stack.iter_mut().rev()
+            .find(|(level, _)| matches!(level, OutlineLevel::Heading(_)));
+        if let Some((_, item)) = heading {
+            append_body(item, line);
+        }
+    }
+    collapse_outline(&mut stack, &mut list.items, OutlineLevel::Heading(0));
+    trim_body(&mut list.items);
+    list.ensure_ids();
+    list.define_missing_statuses();
+    list
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn headlines_and_lists_nest_by_depth_and_indent() {
+        let content = "- loose note\n\
+            * TODO [#A] Plan :work:home:\n\
+            SCHEDULED: <2024-05-01 Wed>\n\
+            :PROPERTIES:\n\
+            :ID: plan\n\
+            :effort: 2h\n\
+            :END:\n\
+            - [X] book\n\
+            \x20 - [ ] confirm\n\
+            - [-] pack\n\
+            ** DONE Sub\n\
+            CLOSED: [2024-04-01 Mon 10:30]\n\
+            * Notes\n";
+        let list = import("plan.org", content);
+        assert_eq!(list.items.len(), 3);
+        assert!(list.items[0].item_type.eq(&ItemType::Note));
+        let plan = &list.items[1];
+        assert_eq!(plan.id, "plan");
+        assert_eq!(plan.priority, Some(1));
+        assert_eq!(plan.tags, vec!["work", "home"]);
+        assert!(plan.due.is_some());
+        assert_eq!(plan.field_text("effort").as_deref(), Some("2h"));
+        let texts = plan.sub_items.iter().map(|item| item.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["book", "pack", "Sub"]);
+        assert!(plan.sub_items[0].status.eq(&ItemStatus::Complete));
+        assert_eq!(plan.sub_items[0].sub_items[0].text, "confirm");
+        assert!(plan.sub_items[1].status.eq(&ItemStatus::InProgress));
+        assert!(plan.sub_items[2].status.eq(&ItemStatus::Complete));
+        assert!(list.items[2].item_type.eq(&ItemType::Heading));
+    }
+    #[test]
+    fn export_round_trips_through_import() {
+        let mut list = List::new("plan.org".to_string());
+        let mut parent = Item::new(ItemType::Todo, "Ship");
+        parent.status = ItemStatus::InProgress;
+        parent.tags.push("release".to_string());
+        let mut child = Item::new(ItemType::Todo, "Tag");
+        child.depends_on.push(parent.id.clone());
+        parent.sub_items.push(child);
+        parent.sub_items.push(Item::new(ItemType::Note, "remember"));
+        list.items.push(parent);
+        let imported = import("plan.org", &export(&list));
+        let ship = &imported.items[0];
+        assert!(ship.status.eq(&ItemStatus::InProgress));
+        assert_eq!(ship.id, list.items[0].id);
+        assert_eq!(ship.sub_items.len(), 2);
+        assert_eq!(ship.sub_items[0].text, "remember");
+        assert_eq!(ship.sub_items[1].depends_on, vec![ship.id.clone()]);
+    }
+    #[test]
+    fn headline_body_survives_a_round_trip() {
+        let content = "* TODO Plan\n\
+            :PROPERTIES:\n\
+            :ID: plan\n\
+            :END:\n\
+            First paragraph\n\
+            \n\
+            \x20 indented line\n\
+            - step\n\
+            closing words\n\
+            \n\
+            ** Sub\n";
+        let list = import("plan.org", content);
+        let plan = &list.items[0];
+        let body = "First paragraph\n\n  indented line\nclosing words";
+        assert_eq!(plan.field_text("body").as_deref(), Some(body));
+        assert_eq!(plan.sub_items[0].text, "step");
+        assert_eq!(plan.sub_items[1].field_text("body"), None);
+        let exported = export(&list);
+        assert!(!exported.contains(":body:"));
+        let imported = import("plan.org", &exported);
+        assert_eq!(imported.items[0].field_text("body").as_deref(), Some(body));
+        assert_eq!(export(&imported), exported);
+    }
+}
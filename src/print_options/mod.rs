@@ -0,0 +1,34 @@
+use {
+    crate::{ filter::{ Filter, FilterContext, }, sort::SortOptions, },
+    std::collections::HashSet,
+};
+#[derive(Clone)]
+pub struct PrintOptions {
+    pub filter: Filter,
+    pub plain: bool,
+    pub max_level: Option<usize>,
+    pub display_hidden: bool,
+    pub sort: Option<SortOptions>,
+    pub verbose: bool,
+}
+impl PrintOptions {
+    pub fn new(
+        filter: Filter, plain: bool, max_level: Option<usize>,
+        display_hidden: bool,
+    ) -> Self {
+        Self {
+            filter,
+            plain,
+            max_level,
+            display_hidden,
+            sort: None,
+            verbose: false,
+        }
+    }
+}
+pub struct PrintContext {
+    pub plain: bool,
+    pub spacing: usize,
+    pub blocked: HashSet<String>,
+    pub filter: FilterContext,
+}
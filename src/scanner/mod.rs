@@ -0,0 +1,387 @@
+use {
+    crate::{
+        enums::{ ExitCode, ItemStatus, ItemType, },
+        item::Item,
+        item_holder::ItemHolder,
+        list::List,
+    },
+    regex::Regex,
+    serde_json::Value,
+    std::{ collections::HashSet, fs, io::ErrorKind, path::Path, },
+};
+const FILE_FIELD: &str = "source_file";
+const LINE_FIELD: &str = "source_line";
+const HASH_FIELD: &str = "source_hash";
+#[derive(Clone)]
+pub struct ScanOptions {
+    pub markers: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+impl ScanOptions {
+    pub fn new() -> Self {
+        Self {
+            markers: vec!["TODO".to_string(), "FIXME".to_string()],
+            include: Vec::new(),
+            exclude: vec!["**/.git/**".to_string(), "**/target/**".to_string()],
+        }
+    }
+}
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[derive(Clone, PartialEq)]
+pub struct TodoComment {
+    pub file: String,
+    pub line: usize,
+    pub marker: String,
+    pub text: String,
+}
+impl TodoComment {
+    pub fn hash(&self) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.marker.bytes().chain([0]).chain(self.text.bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+}
+#[derive(Clone, Default)]
+pub struct Scan {
+    pub files: Vec<String>,
+    pub comments: Vec<TodoComment>,
+}
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub completed: usize,
+    pub reopened: usize,
+}
+fn glob_to_regex(glob: &str) -> Result<Regex, ExitCode> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek().eq(&Some(&'*')) => {
+                chars.next();
+                if chars.peek().eq(&Some(&'/')) {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            },
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).map_err(ExitCode::InvalidSearchPattern)
+}
+struct Globs {
+    patterns: Vec<(Regex, bool)>,
+}
+impl Globs {
+    fn new(globs: &[String]) -> Result<Self, ExitCode> {
+        let patterns = globs.iter()
+            .map(|glob| Ok((glob_to_regex(glob)?, glob.contains('/'))))
+            .collect::<Result<Vec<(Regex, bool)>, ExitCode>>()?;
+        Ok(Self { patterns })
+    }
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+    fn matches(&self, relative: &str) -> bool {
+        let name = relative.trim_end_matches('/').rsplit('/').next().unwrap_or(relative);
+        self.patterns.iter().any(|(pattern, anchored)| match anchored {
+            true => pattern.is_match(relative),
+            false => pattern.is_match(name),
+        })
+    }
+}
+struct Scanner {
+    include: Globs,
+    exclude: Globs,
+    comment: Regex,
+}
+impl Scanner {
+    fn new(options: &ScanOptions) -> Result<Self, ExitCode> {
+        if options.markers.iter().all(|marker| marker.trim().is_empty()) {
+            return Err(ExitCode::NoScanMarkers);
+        }
+        let markers = options.markers.iter()
+            .filter(|marker| !marker.trim().is_empty())
+            .map(|marker| regex::escape(marker.trim()))
+            .collect::<Vec<String>>()
+            .join("|");
+        let comment = Regex::new(&format!(
+            r"(?:^|\s)(?://+!?|#+|/\*+!?|--+|<!--|;+)\s*({})(?:\([^)]*\))?:\s*(.*?)\s*(?:\*/|-->)?\s*$",
+            markers
+        )).map_err(ExitCode::InvalidSearchPattern)?;
+        Ok(Self {
+            include: Globs::new(&options.include)?,
+            exclude: Globs::new(&options.exclude)?,
+            comment,
+        })
+    }
+    fn scan_dir(
+        &self, root: &Path, relative: &str, scan: &mut Scan,
+    ) -> Result<(), ExitCode> {
+        let dir = root.join(relative);
+        let mut entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>(),
+            Err(_) => return Err(ExitCode::FailedToRead(dir)),
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = match relative.is_empty() {
+                true => name,
+                false => format!("{}/{}", relative, name),
+            };
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+            if file_type.is_dir() {
+                if !self.exclude.matches(&format!("{}/", path)) {
+                    self.scan_dir(root, &path, scan)?;
+                }
+            } else if file_type.is_file()
+                && !self.exclude.matches(&path)
+                && (self.include.is_empty() || self.include.matches(&path))
+            {
+                self.scan_file(&entry.path(), &path, scan)?;
+            }
+        }
+        Ok(())
+    }
+    fn scan_file(
+        &self, full: &Path, relative: &str, scan: &mut Scan,
+    ) -> Result<(), ExitCode> {
+        let content = match fs::read_to_string(full) {
+            Ok(content) => content,
+            Err(error) if error.kind().eq(&ErrorKind::InvalidData) => return Ok(()),
+            Err(_) => return Err(ExitCode::FailedToRead(full.to_path_buf())),
+        };
+        scan.files.push(relative.to_string());
+        for (i, line) in content.lines().enumerate() {
+            if let Some(captures) = self.comment.captures(line) {
+                if captures[2].is_empty() {
+                    continue;
+                }
+                scan.comments.push(TodoComment {
+                    file: relative.to_string(),
+                    line: i + 1,
+                    marker: captures[1].to_string(),
+                    text: captures[2].to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+pub fn scan_comments(root: &Path, options: &ScanOptions) -> Result<Scan, ExitCode> {
+    let scanner = Scanner::new(options)?;
+    let mut scan = Scan::default();
+    scanner.scan_dir(root, "", &mut scan)?;
+    Ok(scan)
+}
+struct Tracked {
+    path: Vec<usize>,
+    file: String,
+    text: String,
+    line: Option<u64>,
+    hash: Option<String>,
+    matched: bool,
+}
+fn collect_tracked(items: &[Item], parent: &[usize], tracked: &mut Vec<Tracked>) {
+    for (i, item) in items.iter().enumerate() {
+        let mut path = parent.to_vec();
+        path.push(i + 1);
+        if let Some(file) = item.field_text(FILE_FIELD) {
+            tracked.push(Tracked {
+                path: path.clone(),
+                file,
+                text: item.text.clone(),
+                line: item.fields.get(LINE_FIELD).and_then(|line| line.as_u64()),
+                hash: item.field_text(HASH_FIELD),
+                matched: false,
+            });
+        }
+        collect_tracked(&item.sub_items, &path, tracked);
+    }
+}
+fn similar(a: &str, b: &str) -> bool {
+    let words = |text: &str| text.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<HashSet<String>>();
+    let (a, b) = (words(a), words(b));
+    let shared = a.intersection(&b).count();
+    shared > 0 && shared * 2 >= a.len().max(b.len())
+}
+fn find_match(tracked: &[Tracked], comment: &TodoComment, hash: &str) -> Option<usize> {
+    let candidates = || tracked.iter().enumerate()
+        .filter(|(_, entry)| !entry.matched && entry.file.eq(&comment.file));
+    let distance = |entry: &Tracked| entry.line
+        .map_or(u64::MAX, |line| line.abs_diff(comment.line as u64));
+    candidates()
+        .filter(|(_, entry)| entry.hash.as_deref().eq(&Some(hash)))
+        .min_by_key(|(_, entry)| distance(entry))
+        .or_else(|| candidates()
+            .filter(|(_, entry)| similar(&entry.text, &comment.text))
+            .min_by_key(|(_, entry)| distance(entry)))
+        .map(|(i, _)| i)
+}
+fn stamp(item: &mut Item, comment: &TodoComment, hash: &str) {
+    item.fields.insert(FILE_FIELD.to_string(), Value::String(comment.file.clone()));
+    item.fields.insert(LINE_FIELD.to_string(), Value::from(comment.line));
+    item.fields.insert(HASH_FIELD.to_string(), Value::String(hash.to_string()));
+}
+pub fn sync(list: &mut List, scan: &Scan) -> SyncReport {
+    let mut report = SyncReport::default();
+    let mut tracked = Vec::new();
+    collect_tracked(&list.items, &[], &mut tracked);
+    let mut added = Vec::new();
+    for comment in scan.comments.iter() {
+        let hash = comment.hash();
+        let index = match find_match(&tracked, comment, &hash) {
+            Some(index) => index,
+            None => {
+                let mut item = Item::new(ItemType::Todo, &comment.text);
+                item.tags.push(comment.marker.to_lowercase());
+                stamp(&mut item, comment, &hash);
+                added.push(item);
+                report.added += 1;
+                continue;
+            },
+        };
+        tracked[index].matched = true;
+        let item = match list.get_item_mut(&tracked[index].path) {
+            Some(item) => item,
+            None => continue,
+        };
+        let mut changed = false;
+        if item.status.eq(&ItemStatus::Complete) {
+            item.status = ItemStatus::Incomplete;
+            report.reopened += 1;
+            changed = true;
+        } else if item.text.ne(&comment.text) || tracked[index].line.ne(&Some(comment.line as u64)) {
+            report.updated += 1;
+            changed = true;
+        }
+        if changed {
+            item.text = comment.text.clone();
+            stamp(item, comment, &hash);
+            item.update_date();
+        }
+    }
+    let scanned = scan.files.iter().collect::<HashSet<&String>>();
+    for entry in tracked.iter().filter(|entry| !entry.matched && scanned.contains(&entry.file)) {
+        if let Some(item) = list.get_item_mut(&entry.path) {
+            if item.status.ne(&ItemStatus::Complete) && item.status.ne(&ItemStatus::Disabled) {
+                item.status = ItemStatus::Complete;
+                item.update_date();
+                report.completed += 1;
+            }
+        }
+    }
+    if report.ne(&SyncReport::default()) {
+        list.items.extend(added);
+        list.update_date();
+    }
+    report
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_utils::{ temp_dir, texts, },
+    };
+    fn comment(file: &str, line: usize, text: &str) -> TodoComment {
+        TodoComment {
+            file: file.to_string(),
+            line,
+            marker: "TODO".to_string(),
+            text: text.to_string(),
+        }
+    }
+    #[test]
+    fn empty_markers_are_rejected() {
+        let root = temp_dir("scan-no-markers");
+        let mut options = ScanOptions::new();
+        options.markers = vec![" ".to_string()];
+        assert!(matches!(scan_comments(&root, &options), Err(ExitCode::NoScanMarkers)));
+        options.markers.clear();
+        assert!(matches!(scan_comments(&root, &options), Err(ExitCode::NoScanMarkers)));
+    }
+    #[test]
+    fn markers_must_follow_a_comment_leader() {
+        let root = temp_dir("scan-leaders");
+        fs::write(root.join("main.rs"), "\
+            // TODO: rust line\n\
+            let todo = \"TODO: in a string\";\n\
+            call(); /* FIXME(me): block */\n\
+            log(\"see TODO: later\");\n\
+            /// TODO: doc comment\n").unwrap();
+        fs::write(root.join("run.sh"), "# TODO: shell\necho TODO: not a comment\n").unwrap();
+        fs::write(root.join("page.html"), "<!-- TODO: html -->\n").unwrap();
+        fs::write(root.join("query.sql"), "-- TODO: sql\n").unwrap();
+        fs::write(root.join("init.el"), ";; TODO: lisp\n").unwrap();
+        let scan = scan_comments(&root, &ScanOptions::new()).unwrap();
+        let found = scan.comments.iter()
+            .map(|comment| (comment.file.as_str(), comment.line, comment.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![
+            ("init.el", 1, "lisp"),
+            ("main.rs", 1, "rust line"),
+            ("main.rs", 3, "block"),
+            ("main.rs", 5, "doc comment"),
+            ("page.html", 1, "html"),
+            ("query.sql", 1, "sql"),
+            ("run.sh", 1, "shell"),
+        ]);
+        assert_eq!(scan.files.len(), 5);
+    }
+    #[test]
+    fn only_scanned_files_complete_their_items() {
+        let mut list = List::new("code".to_string());
+        sync(&mut list, &Scan {
+            files: vec!["a.rs".to_string(), "b.rs".to_string()],
+            comments: vec![comment("a.rs", 1, "first"), comment("b.rs", 1, "second")],
+        });
+        assert_eq!(texts(&list.items), vec!["first", "second"]);
+        let report = sync(&mut list, &Scan {
+            files: vec!["a.rs".to_string()],
+            comments: Vec::new(),
+        });
+        assert_eq!(report.completed, 1);
+        assert!(list.items[0].status.eq(&ItemStatus::Complete));
+        assert!(list.items[1].status.eq(&ItemStatus::Incomplete));
+    }
+    #[test]
+    fn unrelated_comment_on_the_same_line_is_not_matched() {
+        let mut list = List::new("code".to_string());
+        sync(&mut list, &Scan {
+            files: vec!["a.rs".to_string()],
+            comments: vec![comment("a.rs", 4, "handle empty input")],
+        });
+        let report = sync(&mut list, &Scan {
+            files: vec!["a.rs".to_string()],
+            comments: vec![comment("a.rs", 4, "rename this module")],
+        });
+        assert_eq!((report.added, report.updated, report.completed), (1, 0, 1));
+        assert_eq!(texts(&list.items), vec!["handle empty input", "rename this module"]);
+        let report = sync(&mut list, &Scan {
+            files: vec!["a.rs".to_string()],
+            comments: vec![comment("a.rs", 6, "rename this module soon")],
+        });
+        assert_eq!((report.added, report.updated), (0, 1));
+        assert_eq!(list.items[1].text, "rename this module soon");
+    }
+}
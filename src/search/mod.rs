@@ -0,0 +1,123 @@
+use {
+    crate::{ enums::{ ExitCode, SearchMode, }, item::Item, },
+    regex::Regex,
+};
+pub struct SearchMatch {
+    pub path: Vec<usize>,
+    pub text: String,
+}
+impl SearchMatch {
+    pub fn indices(&self) -> Vec<usize> {
+        self.path.iter().rev().copied().collect()
+    }
+}
+#[derive(Clone)]
+pub enum Search {
+    Substring(String),
+    CaseInsensitive(String),
+    Regex(Regex),
+}
+impl Search {
+    pub fn new(query: impl AsRef<str>, mode: &SearchMode) -> Result<Self, ExitCode> {
+        let query = query.as_ref();
+        match mode {
+            SearchMode::Substring => Ok(Self::Substring(query.to_string())),
+            SearchMode::CaseInsensitive => {
+                Ok(Self::CaseInsensitive(query.to_lowercase()))
+            },
+            SearchMode::Regex => {
+                match Regex::new(query) {
+                    Ok(re) => Ok(Self::Regex(re)),
+                    Err(e) => Err(ExitCode::InvalidSearchPattern(e)),
+                }
+            },
+        }
+    }
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Substring(query) => text.contains(query.as_str()),
+            Self::CaseInsensitive(query) => {
+                text.to_lowercase().contains(query.as_str())
+            },
+            Self::Regex(re) => re.is_match(text),
+        }
+    }
+    pub fn collect(
+        &self, items: &[Item], path: &mut Vec<usize>,
+        matches: &mut Vec<SearchMatch>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            path.push(i + 1);
+            if self.is_match(&item.text) {
+                matches.push(SearchMatch {
+                    path: path.clone(),
+                    text: item.text.clone(),
+                });
+            }
+            self.collect(&item.sub_items, path, matches);
+            path.pop();
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ enums::ItemType, item_holder::{ ItemAction, ItemActor, }, list::List, },
+    };
+    fn sample() -> List {
+        let mut list = List::new("sample".to_string());
+        let mut parent = Item::new(ItemType::Todo, "Write report");
+        parent.sub_items.push(Item::new(ItemType::Todo, "collect figures"));
+        parent.sub_items.push(Item::new(ItemType::Note, "REPORT draft"));
+        list.items.push(parent);
+        list.items.push(Item::new(ItemType::Todo, "Call Bob"));
+        list
+    }
+    #[test]
+    fn substring_is_case_sensitive() {
+        let matches = sample().search("REPORT", &SearchMode::Substring).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, vec![1, 2]);
+        assert!(sample().search("bob", &SearchMode::Substring).unwrap().is_empty());
+    }
+    #[test]
+    fn case_insensitive_matches_nested_items() {
+        let matches = sample().search("report", &SearchMode::CaseInsensitive).unwrap();
+        let paths = matches.iter().map(|m| m.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![vec![1], vec![1, 2]]);
+    }
+    #[test]
+    fn regex_matches_and_rejects_invalid_patterns() {
+        let matches = sample().search("^c", &SearchMode::Regex).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "collect figures");
+        assert!(matches!(
+            Search::new("(", &SearchMode::Regex),
+            Err(ExitCode::InvalidSearchPattern(_)),
+        ));
+    }
+    #[test]
+    fn indices_are_reversed_for_act_on_item_at() {
+        let mut list = sample();
+        let matches = list.search("figures", &SearchMode::Substring).unwrap();
+        assert_eq!(matches[0].path, vec![1, 1]);
+        let mut indices = matches[0].indices();
+        assert_eq!(indices, vec![1, 1]);
+        let matches = list.search("draft", &SearchMode::Substring).unwrap();
+        indices = matches[0].indices();
+        assert_eq!(indices, vec![2, 1]);
+        let removed = list.act_on_item_at(&mut indices, ItemAction::Remove);
+        assert_eq!(removed.unwrap().text, "REPORT draft");
+    }
+    #[test]
+    fn print_matches_skips_hidden_items() {
+        let mut list = sample();
+        list.items[0].hidden = true;
+        let matches = list.search("r", &SearchMode::CaseInsensitive).unwrap();
+        let mut output = String::new();
+        list.print_matches(&mut output, &matches, true);
+        assert!(!output.contains("Write report"));
+        assert!(!output.contains("REPORT draft"));
+    }
+}
@@ -0,0 +1,103 @@
+use {
+    crate::{ enums::{ ItemStatus, SortKey, }, item::Item, },
+    std::cmp::Ordering,
+};
+#[derive(Clone)]
+pub struct SortOptions {
+    pub key: SortKey,
+    pub descending: bool,
+    pub recursive: bool,
+}
+impl SortOptions {
+    pub fn new(key: SortKey, descending: bool, recursive: bool) -> Self {
+        Self { key, descending, recursive, }
+    }
+    fn status_rank(status: &ItemStatus) -> u8 {
+        match status {
+            ItemStatus::Incomplete => 0,
+            ItemStatus::InProgress => 1,
+            ItemStatus::Waiting => 2,
+            ItemStatus::Blocked => 3,
+            ItemStatus::Custom(_) => 4,
+            ItemStatus::Disabled => 5,
+            ItemStatus::Complete => 6,
+        }
+    }
+    pub fn compare(&self, a: &Item, b: &Item) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Created => a.created.cmp(&b.created),
+            SortKey::Updated => a.last_updated.cmp(&b.last_updated),
+            SortKey::Status => {
+                Self::status_rank(&a.status).cmp(&Self::status_rank(&b.status))
+            },
+            SortKey::Text => a.text.to_lowercase().cmp(&b.text.to_lowercase()),
+            SortKey::Priority => {
+                a.priority.unwrap_or(u8::MAX).cmp(&b.priority.unwrap_or(u8::MAX))
+            },
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+    pub fn order(&self, items: &[Item]) -> Vec<usize> {
+        let mut order = (0..items.len()).collect::<Vec<usize>>();
+        order.sort_by(|a, b| self.compare(&items[*a], &items[*b]));
+        order
+    }
+    pub fn sort(&self, items: &mut [Item]) {
+        items.sort_by(|a, b| self.compare(a, b));
+        if self.recursive {
+            for item in items.iter_mut() {
+                self.sort(&mut item.sub_items);
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ enums::ItemType, test_utils::texts, },
+    };
+    fn sample() -> Vec<Item> {
+        let mut first = Item::new(ItemType::Todo, "banana");
+        first.status = ItemStatus::Complete;
+        first.sub_items.push(Item::new(ItemType::Todo, "b"));
+        first.sub_items.push(Item::new(ItemType::Todo, "a"));
+        let mut second = Item::new(ItemType::Todo, "Apple");
+        second.priority = Some(3);
+        let mut third = Item::new(ItemType::Todo, "cherry");
+        third.priority = Some(1);
+        vec![first, second, third]
+    }
+    #[test]
+    fn sorts_by_text_ignoring_case() {
+        let mut items = sample();
+        SortOptions::new(SortKey::Text, false, false).sort(&mut items);
+        assert_eq!(texts(&items), vec!["Apple", "banana", "cherry"]);
+        assert_eq!(texts(&items[1].sub_items), vec!["b", "a"]);
+    }
+    #[test]
+    fn recursive_sort_reaches_sub_items() {
+        let mut items = sample();
+        SortOptions::new(SortKey::Text, true, true).sort(&mut items);
+        assert_eq!(texts(&items), vec!["cherry", "banana", "Apple"]);
+        assert_eq!(texts(&items[1].sub_items), vec!["b", "a"]);
+        SortOptions::new(SortKey::Text, false, true).sort(&mut items);
+        assert_eq!(texts(&items[1].sub_items), vec!["a", "b"]);
+    }
+    #[test]
+    fn missing_priorities_sort_last() {
+        let items = sample();
+        let order = SortOptions::new(SortKey::Priority, false, false).order(&items);
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+    #[test]
+    fn status_sort_puts_open_items_first_and_is_stable() {
+        let items = sample();
+        let order = SortOptions::new(SortKey::Status, false, false).order(&items);
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{ Deserialize, Serialize, };
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct StatusDefinition {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+}
+impl StatusDefinition {
+    pub fn new(
+        name: impl AsRef<str>, symbol: impl AsRef<str>, color: Option<String>,
+        done: bool,
+    ) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            symbol: symbol.as_ref().to_string(),
+            color,
+            done,
+        }
+    }
+}
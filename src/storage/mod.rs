@@ -0,0 +1,216 @@
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+use {
+    crate::{
+        enums::{ ExitCode, ListFormat, },
+        item_holder::ItemAction,
+        list::List,
+    },
+    std::{
+        fs::{ self, File, OpenOptions, },
+        io::{ Read, Write, },
+        path::{ Path, PathBuf, },
+    },
+};
+pub trait Storage {
+    fn exists(&self) -> bool;
+    fn create(&mut self, name: &str) -> Result<List, ExitCode>;
+    fn load(&self) -> Result<List, ExitCode>;
+    fn save(&mut self, list: &List) -> Result<(), ExitCode>;
+    fn file_path(&self) -> Option<&Path> {
+        None
+    }
+    fn applies_actions(&self) -> bool {
+        false
+    }
+    fn apply_action(
+        &mut self, _list: &List, _path: &[usize], _action: &ItemAction,
+    ) -> Result<(), ExitCode> {
+        Ok(())
+    }
+}
+pub struct FileStorage {
+    pub path: PathBuf,
+    pub format: ListFormat,
+}
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let format = ListFormat::from_path(&path);
+        Self { path, format, }
+    }
+    pub fn with_format(path: impl Into<PathBuf>, format: ListFormat) -> Self {
+        Self { path: path.into(), format, }
+    }
+    fn temp_path(&self) -> PathBuf {
+        let name = match self.path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => String::from("list"),
+        };
+        self.path.with_file_name(format!(".{}.tmp", name))
+    }
+}
+impl Storage for FileStorage {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+    fn file_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+    fn create(&mut self, name: &str) -> Result<List, ExitCode> {
+        if self.path.exists() {
+            return Err(ExitCode::FileExists(self.path.clone()));
+        }
+        { // file creation
+            match File::create(&self.path) {
+                Ok(_) => {},
+                Err(_) => return Err(ExitCode::FailedToOpen(self.path.clone())),
+            }
+        } // file unlocked
+        Ok(List::new(name.to_string()))
+    }
+    fn load(&self) -> Result<List, ExitCode> {
+        let mut json = String::new();
+        { // file read
+            let mut file = match OpenOptions::new()
+                .read(true)
+                .open(&self.path)
+            {
+                Ok(f) => f,
+                Err(_) => return Err(ExitCode::FailedToOpen(self.path.clone())),
+            };
+            match file.read_to_string(&mut json) {
+                Ok(_) => {},
+                Err(_) => {
+                    return Err(ExitCode::FailedToRead(self.path.clone()));
+                },
+            }
+        } // file locked
+        List::from_format(json, self.format)
+    }
+    fn save(&mut self, list: &List) -> Result<(), ExitCode> {
+        let json = list.to_format(self.format)?;
+        if !self.path.is_file() {
+            return Err(ExitCode::FailedToOpen(self.path.clone()));
+        }
+        let temp = self.temp_path();
+        { // file open:write
+            let bytes = json.as_bytes();
+            let mut file = match File::create(&temp) {
+                Ok(f) => f,
+                Err(_) => return Err(ExitCode::FailedToOpen(temp)),
+            };
+            let permissions = fs::metadata(&self.path).map(|metadata| metadata.permissions());
+            if permissions.and_then(|permissions| file.set_permissions(permissions)).is_err() {
+                let _ = fs::remove_file(&temp);
+                return Err(ExitCode::FailedToWrite(self.path.clone()));
+            }
+            if file.write_all(bytes).and_then(|_| file.sync_all()).is_err() {
+                let _ = fs::remove_file(&temp);
+                return Err(ExitCode::FailedToWrite(self.path.clone()));
+            }
+        } // file locked
+        if fs::rename(&temp, &self.path).is_err() {
+            let _ = fs::remove_file(&temp);
+            return Err(ExitCode::FailedToWrite(self.path.clone()));
+        }
+        Ok(())
+    }
+}
+#[derive(Default)]
+pub struct MemoryStorage {
+    pub list: Option<List>,
+}
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self { list: None, }
+    }
+    pub fn with_list(list: List) -> Self {
+        Self { list: Some(list), }
+    }
+}
+impl Storage for MemoryStorage {
+    fn exists(&self) -> bool {
+        self.list.is_some()
+    }
+    fn create(&mut self, name: &str) -> Result<List, ExitCode> {
+        if let Some(list) = &self.list {
+            return Err(ExitCode::ListExists(list.name.clone()));
+        }
+        let list = List::new(name.to_string());
+        self.list = Some(list.clone());
+        Ok(list)
+    }
+    fn load(&self) -> Result<List, ExitCode> {
+        match &self.list {
+            Some(list) => Ok(list.clone()),
+            None => Err(ExitCode::ListNotFound),
+        }
+    }
+    fn save(&mut self, list: &List) -> Result<(), ExitCode> {
+        self.list = Some(list.clone());
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ enums::ItemType, item::Item, test_utils::temp_dir, },
+    };
+    #[test]
+    fn file_storage_saves_through_a_temp_file() {
+        let dir = temp_dir("storage-atomic");
+        let mut storage = FileStorage::new(dir.join("list.yaml"));
+        assert!(!storage.exists());
+        let mut list = storage.create("chores").unwrap();
+        assert!(storage.exists());
+        list.items.push(Item::new(ItemType::Todo, "sweep"));
+        storage.save(&list).unwrap();
+        assert!(!storage.temp_path().exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert!(fs::read_to_string(&storage.path).unwrap().contains("sweep"));
+        let loaded = storage.load().unwrap();
+        assert_eq!(loaded.name, "chores");
+        assert_eq!(loaded.items[0].text, "sweep");
+    }
+    #[cfg(unix)]
+    #[test]
+    fn file_storage_keeps_the_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir("storage-permissions");
+        let mut storage = FileStorage::new(dir.join("list.json"));
+        let list = storage.create("private").unwrap();
+        fs::set_permissions(&storage.path, fs::Permissions::from_mode(0o600)).unwrap();
+        storage.save(&list).unwrap();
+        let mode = fs::metadata(&storage.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    #[test]
+    fn file_storage_refuses_to_overwrite_or_recreate() {
+        let dir = temp_dir("storage-exists");
+        let mut storage = FileStorage::new(dir.join("list.json"));
+        storage.create("first").unwrap();
+        assert!(matches!(storage.create("second"), Err(ExitCode::FileExists(_))));
+        let mut missing = FileStorage::new(dir.join("missing.json"));
+        let list = List::new("missing".to_string());
+        assert!(matches!(missing.save(&list), Err(ExitCode::FailedToOpen(_))));
+        assert!(!missing.exists());
+        assert!(matches!(missing.load(), Err(ExitCode::FailedToOpen(_))));
+    }
+    #[test]
+    fn memory_storage_keeps_the_last_saved_list() {
+        let mut storage = MemoryStorage::new();
+        assert!(!storage.exists());
+        assert!(matches!(storage.load(), Err(ExitCode::ListNotFound)));
+        assert!(storage.file_path().is_none());
+        let mut list = storage.create("scratch").unwrap();
+        assert!(matches!(storage.create("again"), Err(ExitCode::ListExists(_))));
+        list.items.push(Item::new(ItemType::Note, "idea"));
+        assert_eq!(storage.load().unwrap().items.len(), 0);
+        storage.save(&list).unwrap();
+        assert_eq!(storage.load().unwrap().items[0].text, "idea");
+    }
+}
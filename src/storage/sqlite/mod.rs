@@ -0,0 +1,638 @@
+use {
+    crate::{
+        enums::ExitCode,
+        item::Item,
+        item_holder::ItemAction,
+        list::List,
+        storage::Storage,
+    },
+    rusqlite::{ params, Connection, OptionalExtension, Transaction, },
+    serde::{ de::DeserializeOwned, Serialize, },
+    serde_json::Value,
+    std::{ collections::HashMap, path::Path, },
+};
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS lists (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        name TEXT NOT NULL,
+        created TEXT NOT NULL,
+        last_updated TEXT NOT NULL,
+        status_cycle TEXT NOT NULL,
+        statuses TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS items (
+        row INTEGER PRIMARY KEY,
+        id TEXT NOT NULL,
+        parent_row INTEGER REFERENCES items(row) ON DELETE CASCADE,
+        occurrence INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        item_type TEXT NOT NULL,
+        status TEXT NOT NULL,
+        text TEXT NOT NULL,
+        created TEXT NOT NULL,
+        last_updated TEXT NOT NULL,
+        hidden INTEGER NOT NULL,
+        priority INTEGER,
+        due TEXT,
+        recurrence TEXT,
+        estimate TEXT,
+        link TEXT,
+        archived_from TEXT,
+        archived_parent TEXT
+    );
+    CREATE INDEX IF NOT EXISTS items_parent ON items(parent_row, occurrence, position);
+    CREATE TABLE IF NOT EXISTS item_values (
+        item_row INTEGER NOT NULL REFERENCES items(row) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        key TEXT,
+        value TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS item_values_item ON item_values(item_row, kind, position);
+";
+const ITEM_COLUMNS: &str = "id, item_type, status, text, created, last_updated,
+    hidden, priority, due, recurrence, estimate, link, archived_from, archived_parent";
+fn database_error(e: rusqlite::Error) -> ExitCode {
+    ExitCode::DatabaseError(e)
+}
+fn to_name(value: &impl Serialize) -> Result<String, ExitCode> {
+    match serde_json::to_value(value) {
+        Ok(Value::String(name)) => Ok(name),
+        Ok(value) => Ok(value.to_string()),
+        Err(e) => Err(ExitCode::FailedToSerialize(e)),
+    }
+}
+fn from_name<T: DeserializeOwned>(name: String) -> Result<T, ExitCode> {
+    match serde_json::from_value(Value::String(name)) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(ExitCode::FailedToDeserialize(e)),
+    }
+}
+fn to_text(value: &impl Serialize) -> Result<String, ExitCode> {
+    match serde_json::to_string(value) {
+        Ok(text) => Ok(text),
+        Err(e) => Err(ExitCode::FailedToSerialize(e)),
+    }
+}
+fn from_text<T: DeserializeOwned>(text: &str) -> Result<T, ExitCode> {
+    match serde_json::from_str(text) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(ExitCode::FailedToDeserialize(e)),
+    }
+}
+fn optional_text(value: &Option<impl Serialize>) -> Result<Option<String>, ExitCode> {
+    value.as_ref().map(to_text).transpose()
+}
+fn optional_from_text<T: DeserializeOwned>(
+    text: Option<String>,
+) -> Result<Option<T>, ExitCode> {
+    text.as_deref().map(from_text).transpose()
+}
+type ValueRow<'a> = (&'a str, Option<&'a str>, String);
+struct ItemRow {
+    row: i64,
+    parent_row: Option<i64>,
+    occurrence: bool,
+    item: Item,
+}
+pub struct SqliteStorage {
+    connection: Connection,
+}
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ExitCode> {
+        let connection = Connection::open(path).map_err(database_error)?;
+        Self::with_connection(connection)
+    }
+    pub fn open_in_memory() -> Result<Self, ExitCode> {
+        let connection = Connection::open_in_memory().map_err(database_error)?;
+        Self::with_connection(connection)
+    }
+    fn with_connection(connection: Connection) -> Result<Self, ExitCode> {
+        connection.execute_batch(SCHEMA).map_err(database_error)?;
+        Ok(Self { connection, })
+    }
+    pub fn import_json(&mut self, json: String) -> Result<List, ExitCode> {
+        let list = List::from_json(json)?;
+        self.save(&list)?;
+        Ok(list)
+    }
+    pub fn export_json(&self) -> Result<String, ExitCode> {
+        self.load()?.to_json()
+    }
+    fn write_list(transaction: &Transaction, list: &List) -> Result<(), ExitCode> {
+        transaction.execute(
+            "INSERT INTO lists (id, name, created, last_updated, status_cycle, statuses)
+            VALUES (1, ?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(id) DO UPDATE SET name = excluded.name,
+                created = excluded.created,
+                last_updated = excluded.last_updated,
+                status_cycle = excluded.status_cycle,
+                statuses = excluded.statuses",
+            params![
+                list.name, to_name(&list.created)?, to_name(&list.last_updated)?,
+                to_text(&list.status_cycle)?, to_text(&list.statuses)?,
+            ],
+        ).map_err(database_error)?;
+        Ok(())
+    }
+    fn item_values(item: &Item) -> Result<Vec<ValueRow<'_>>, ExitCode> {
+        let mut values: Vec<ValueRow> = Vec::new();
+        values.extend(item.tags.iter().map(|tag| ("tag", None, tag.clone())));
+        values.extend(item.depends_on.iter().map(|id| ("depends_on", None, id.clone())));
+        values.extend(item.assignees.iter().map(|user| ("assignee", None, user.clone())));
+        for entry in item.time_entries.iter() {
+            values.push(("time_entry", None, to_text(entry)?));
+        }
+        for (key, value) in item.fields.iter() {
+            values.push(("field", Some(key.as_str()), to_text(value)?));
+        }
+        Ok(values)
+    }
+    fn insert_values(
+        transaction: &Transaction, row: i64, values: Vec<ValueRow>,
+    ) -> Result<(), ExitCode> {
+        let mut positions: HashMap<&str, i64> = HashMap::new();
+        for (kind, key, value) in values.into_iter() {
+            let position = positions.entry(kind).or_default();
+            transaction.execute(
+                "INSERT INTO item_values (item_row, kind, position, key, value)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![row, kind, *position, key, value],
+            ).map_err(database_error)?;
+            *position += 1;
+        }
+        Ok(())
+    }
+    fn write_values(
+        transaction: &Transaction, row: i64, item: &Item,
+    ) -> Result<(), ExitCode> {
+        transaction.execute("DELETE FROM item_values WHERE item_row = ?1", params![row])
+            .map_err(database_error)?;
+        Self::insert_values(transaction, row, Self::item_values(item)?)
+    }
+    fn write_kind(
+        transaction: &Transaction, row: i64, item: &Item, kind: &str,
+    ) -> Result<(), ExitCode> {
+        transaction.execute(
+            "DELETE FROM item_values WHERE item_row = ?1 AND kind = ?2", params![row, kind],
+        ).map_err(database_error)?;
+        let values = Self::item_values(item)?.into_iter()
+            .filter(|(value_kind, _, _)| value_kind.eq(&kind))
+            .collect();
+        Self::insert_values(transaction, row, values)
+    }
+    fn write_field(
+        transaction: &Transaction, row: i64, item: &Item, key: &str,
+    ) -> Result<(), ExitCode> {
+        transaction.execute(
+            "DELETE FROM item_values WHERE item_row = ?1 AND kind = 'field' AND key = ?2",
+            params![row, key],
+        ).map_err(database_error)?;
+        if let Some(value) = item.fields.get(key) {
+            let position = item.fields.keys().take_while(|field| field.as_str().ne(key)).count();
+            transaction.execute(
+                "INSERT INTO item_values (item_row, kind, position, key, value)
+                VALUES (?1, 'field', ?2, ?3, ?4)",
+                params![row, position as i64, key, to_text(value)?],
+            ).map_err(database_error)?;
+        }
+        Ok(())
+    }
+    fn insert_item(
+        transaction: &Transaction, item: &Item, parent_row: Option<i64>,
+        occurrence: bool, position: usize,
+    ) -> Result<(), ExitCode> {
+        transaction.execute(
+            &format!(
+                "INSERT INTO items (parent_row, occurrence, position, {})
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                    ?16, ?17)",
+                ITEM_COLUMNS,
+            ),
+            params![
+                parent_row, occurrence, position as i64, item.id,
+                to_name(&item.item_type)?, to_name(&item.status)?, item.text,
+                to_name(&item.created)?, to_name(&item.last_updated)?, item.hidden,
+                item.priority, item.due.as_ref().map(to_name).transpose()?,
+                optional_text(&item.recurrence)?, optional_text(&item.estimate)?,
+                item.link, optional_text(&item.archived_from)?, item.archived_parent,
+            ],
+        ).map_err(database_error)?;
+        let row = transaction.last_insert_rowid();
+        Self::write_values(transaction, row, item)?;
+        Self::insert_children(transaction, row, item)
+    }
+    fn insert_children(
+        transaction: &Transaction, row: i64, item: &Item,
+    ) -> Result<(), ExitCode> {
+        for (position, sub_item) in item.sub_items.iter().enumerate() {
+            Self::insert_item(transaction, sub_item, Some(row), false, position)?;
+        }
+        for (position, completed) in item.occurrences.iter().enumerate() {
+            Self::insert_item(transaction, completed, Some(row), true, position)?;
+        }
+        Ok(())
+    }
+    fn update_row(
+        transaction: &Transaction, row: i64, item: &Item,
+    ) -> Result<(), ExitCode> {
+        transaction.execute(
+            "UPDATE items SET id = ?2, item_type = ?3, status = ?4, text = ?5,
+                created = ?6, last_updated = ?7, hidden = ?8, priority = ?9, due = ?10,
+                recurrence = ?11, estimate = ?12, link = ?13, archived_from = ?14,
+                archived_parent = ?15
+            WHERE row = ?1",
+            params![
+                row, item.id, to_name(&item.item_type)?, to_name(&item.status)?,
+                item.text, to_name(&item.created)?, to_name(&item.last_updated)?,
+                item.hidden, item.priority, item.due.as_ref().map(to_name).transpose()?,
+                optional_text(&item.recurrence)?, optional_text(&item.estimate)?,
+                item.link, optional_text(&item.archived_from)?, item.archived_parent,
+            ],
+        ).map_err(database_error)?;
+        Ok(())
+    }
+    fn find_rows(
+        transaction: &Transaction, path: &[usize],
+    ) -> Result<Option<Vec<i64>>, ExitCode> {
+        let mut rows = Vec::new();
+        for index in path.iter() {
+            if index.eq(&0) {
+                return Ok(None);
+            }
+            let row = transaction.query_row(
+                "SELECT row FROM items
+                WHERE parent_row IS ?1 AND occurrence = 0 AND position = ?2",
+                params![rows.last(), (*index - 1) as i64],
+                |row| row.get::<_, i64>(0),
+            ).optional().map_err(database_error)?;
+            match row {
+                Some(row) => rows.push(row),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(rows))
+    }
+    fn read_item_rows(&self) -> Result<Vec<ItemRow>, ExitCode> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT row, parent_row, occurrence, {} FROM items
+            ORDER BY parent_row, occurrence, position",
+            ITEM_COLUMNS,
+        )).map_err(database_error)?;
+        let columns = statement.query_map([], |row| Ok((
+            (row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, bool>(2)?),
+            (row.get::<_, String>(3)?, row.get::<_, String>(4)?, row.get::<_, String>(5)?),
+            (row.get::<_, String>(6)?, row.get::<_, String>(7)?, row.get::<_, String>(8)?),
+            (row.get::<_, bool>(9)?, row.get::<_, Option<u8>>(10)?),
+            (row.get::<_, Option<String>>(11)?, row.get::<_, Option<String>>(12)?),
+            (row.get::<_, Option<String>>(13)?, row.get::<_, Option<String>>(14)?),
+            (row.get::<_, Option<String>>(15)?, row.get::<_, Option<String>>(16)?),
+        ))).map_err(database_error)?;
+        let mut rows = Vec::new();
+        for columns in columns {
+            let (
+                (row, parent_row, occurrence), (id, item_type, status),
+                (text, created, last_updated), (hidden, priority), (due, recurrence),
+                (estimate, link), (archived_from, archived_parent),
+            ) = columns.map_err(database_error)?;
+            let mut item = Item::new(from_name(item_type)?, text);
+            item.id = id;
+            item.status = from_name(status)?;
+            item.created = from_name(created)?;
+            item.last_updated = from_name(last_updated)?;
+            item.hidden = hidden;
+            item.priority = priority;
+            item.due = due.map(from_name).transpose()?;
+            item.recurrence = optional_from_text(recurrence)?;
+            item.estimate = optional_from_text(estimate)?;
+            item.link = link;
+            item.archived_from = optional_from_text(archived_from)?;
+            item.archived_parent = archived_parent;
+            rows.push(ItemRow { row, parent_row, occurrence, item, });
+        }
+        Ok(rows)
+    }
+    fn read_values(&self, items: &mut HashMap<i64, Item>) -> Result<(), ExitCode> {
+        let mut statement = self.connection.prepare(
+            "SELECT item_row, kind, key, value FROM item_values
+            ORDER BY item_row, kind, position",
+        ).map_err(database_error)?;
+        let values = statement.query_map([], |row| Ok((
+            row.get::<_, i64>(0)?, row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?, row.get::<_, String>(3)?,
+        ))).map_err(database_error)?;
+        for value in values {
+            let (row, kind, key, value) = value.map_err(database_error)?;
+            let item = match items.get_mut(&row) {
+                Some(item) => item,
+                None => continue,
+            };
+            match kind.as_str() {
+                "tag" => item.tags.push(value),
+                "depends_on" => item.depends_on.push(value),
+                "assignee" => item.assignees.push(value),
+                "time_entry" => item.time_entries.push(from_text(&value)?),
+                "field" => {
+                    item.fields.insert(key.unwrap_or_default(), from_text(&value)?);
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+    fn build_items(
+        parent_row: Option<i64>, occurrence: bool,
+        children: &HashMap<(Option<i64>, bool), Vec<i64>>,
+        items: &mut HashMap<i64, Item>,
+    ) -> Vec<Item> {
+        let rows = match children.get(&(parent_row, occurrence)) {
+            Some(rows) => rows,
+            None => return Vec::new(),
+        };
+        rows.iter()
+            .filter_map(|row| {
+                let mut item = items.remove(row)?;
+                item.sub_items = Self::build_items(Some(*row), false, children, items);
+                item.occurrences = Self::build_items(Some(*row), true, children, items);
+                Some(item)
+            })
+            .collect()
+    }
+    fn write_action(
+        &mut self, list: &List, path: &[usize], action: &ItemAction,
+    ) -> Result<(), ExitCode> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        let parent_path = match action {
+            ItemAction::Add(_, _) | ItemAction::Put(_) => path,
+            _ => match path.split_last() {
+                Some((_, parent)) => parent,
+                None => return Ok(()),
+            },
+        };
+        let parent_rows = match Self::find_rows(&transaction, parent_path)? {
+            Some(rows) => rows,
+            None => return Ok(()),
+        };
+        let parent_row = parent_rows.last().copied();
+        match action {
+            ItemAction::Add(_, _) | ItemAction::Put(_) => {
+                let siblings = match list.get_item(parent_path) {
+                    Some(parent) => &parent.sub_items,
+                    None => &list.items,
+                };
+                if let Some(item) = siblings.last() {
+                    Self::insert_item(
+                        &transaction, item, parent_row, false, siblings.len() - 1,
+                    )?;
+                }
+            },
+            ItemAction::Remove => {
+                let position = match path[path.len() - 1].checked_sub(1) {
+                    Some(position) => position as i64,
+                    None => return Ok(()),
+                };
+                transaction.execute(
+                    "DELETE FROM items
+                    WHERE parent_row IS ?1 AND occurrence = 0 AND position = ?2",
+                    params![parent_row, position],
+                ).map_err(database_error)?;
+                transaction.execute(
+                    "UPDATE items SET position = position - 1
+                    WHERE parent_row IS ?1 AND occurrence = 0 AND position > ?2",
+                    params![parent_row, position],
+                ).map_err(database_error)?;
+            },
+            _ => {
+                let row = match Self::find_rows(&transaction, path)? {
+                    Some(rows) => rows[rows.len() - 1],
+                    None => return Ok(()),
+                };
+                let item = match list.get_item(path) {
+                    Some(item) => item,
+                    None => return Ok(()),
+                };
+                Self::update_row(&transaction, row, item)?;
+                match action {
+                    ItemAction::Replace(_) => {
+                        Self::write_values(&transaction, row, item)?;
+                        transaction.execute(
+                            "DELETE FROM items WHERE parent_row = ?1", params![row],
+                        ).map_err(database_error)?;
+                        Self::insert_children(&transaction, row, item)?;
+                    },
+                    ItemAction::AddTag(_) | ItemAction::RemoveTag(_) => {
+                        Self::write_kind(&transaction, row, item, "tag")?;
+                    },
+                    ItemAction::AddDependency(_) | ItemAction::RemoveDependency(_) => {
+                        Self::write_kind(&transaction, row, item, "depends_on")?;
+                    },
+                    ItemAction::Assign(_) | ItemAction::Unassign(_) => {
+                        Self::write_kind(&transaction, row, item, "assignee")?;
+                    },
+                    ItemAction::StartTimer(_) | ItemAction::StopTimer => {
+                        Self::write_kind(&transaction, row, item, "time_entry")?;
+                    },
+                    ItemAction::SetField(key, _) | ItemAction::UnsetField(key) => {
+                        Self::write_field(&transaction, row, item, key)?;
+                    },
+                    _ => {},
+                }
+            },
+        }
+        for (depth, row) in parent_rows.iter().enumerate() {
+            if let Some(item) = list.get_item(&parent_path[..depth + 1]) {
+                Self::update_row(&transaction, *row, item)?;
+            }
+        }
+        Self::write_list(&transaction, list)?;
+        transaction.commit().map_err(database_error)
+    }
+}
+impl Storage for SqliteStorage {
+    fn exists(&self) -> bool {
+        self.connection.query_row("SELECT 1 FROM lists WHERE id = 1", [], |_| Ok(()))
+            .optional()
+            .is_ok_and(|row| row.is_some())
+    }
+    fn create(&mut self, name: &str) -> Result<List, ExitCode> {
+        if self.exists() {
+            return Err(ExitCode::ListExists(name.to_string()));
+        }
+        let list = List::new(name.to_string());
+        self.save(&list)?;
+        Ok(list)
+    }
+    fn load(&self) -> Result<List, ExitCode> {
+        let list_row = self.connection.query_row(
+            "SELECT name, created, last_updated, status_cycle, statuses
+            FROM lists WHERE id = 1",
+            [],
+            |row| Ok((
+                row.get::<_, String>(0)?, row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?, row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            )),
+        ).optional().map_err(database_error)?;
+        let (name, created, last_updated, status_cycle, statuses) = match list_row {
+            Some(row) => row,
+            None => return Err(ExitCode::ListNotFound),
+        };
+        let mut list = List::new(name);
+        list.created = from_name(created)?;
+        list.last_updated = from_name(last_updated)?;
+        list.status_cycle = from_text(&status_cycle)?;
+        list.statuses = from_text(&statuses)?;
+        let mut children: HashMap<(Option<i64>, bool), Vec<i64>> = HashMap::new();
+        let mut items = HashMap::new();
+        for item_row in self.read_item_rows()?.into_iter() {
+            children.entry((item_row.parent_row, item_row.occurrence))
+                .or_default()
+                .push(item_row.row);
+            items.insert(item_row.row, item_row.item);
+        }
+        self.read_values(&mut items)?;
+        list.items = Self::build_items(None, false, &children, &mut items);
+        list.define_missing_statuses();
+        Ok(list)
+    }
+    fn save(&mut self, list: &List) -> Result<(), ExitCode> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        transaction.execute("DELETE FROM item_values", []).map_err(database_error)?;
+        transaction.execute("DELETE FROM items", []).map_err(database_error)?;
+        Self::write_list(&transaction, list)?;
+        for (position, item) in list.items.iter().enumerate() {
+            Self::insert_item(&transaction, item, None, false, position)?;
+        }
+        transaction.commit().map_err(database_error)
+    }
+    fn applies_actions(&self) -> bool {
+        true
+    }
+    fn apply_action(
+        &mut self, list: &List, path: &[usize], action: &ItemAction,
+    ) -> Result<(), ExitCode> {
+        self.write_action(list, path, action)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            container::Container,
+            enums::{ Estimate, ItemStatus, ItemType, Recurrence, },
+            item_holder::ItemActor,
+            test_utils::temp_dir,
+            time_entry::TimeEntry,
+        },
+        chrono::{ Local, Weekday, },
+    };
+    fn total_changes(storage: &SqliteStorage) -> i64 {
+        storage.connection.query_row("SELECT total_changes()", [], |row| row.get(0)).unwrap()
+    }
+    fn sample() -> List {
+        let mut list = List::new("sqlite".to_string());
+        let mut parent = Item::new(ItemType::Todo, "parent");
+        parent.tags = vec!["work".to_string(), "home".to_string()];
+        parent.priority = Some(2);
+        parent.due = Some(Local::now());
+        parent.recurrence = Some(Recurrence::Weekly(vec![Weekday::Mon]));
+        parent.estimate = Some(Estimate::Points(1.5));
+        parent.link = Some("https://example.com".to_string());
+        parent.assignees.push("sam".to_string());
+        parent.fields.insert("size".to_string(), Value::from(3));
+        parent.time_entries.push(TimeEntry::new(Some("focus".to_string())));
+        parent.status = ItemStatus::Blocked;
+        let mut child = Item::new(ItemType::Note, "child");
+        child.id = parent.id.clone();
+        child.hidden = true;
+        parent.sub_items.push(child);
+        let mut done = Item::new(ItemType::Todo, "done");
+        done.status = ItemStatus::Complete;
+        parent.occurrences.push(done);
+        list.items.push(parent);
+        for i in 0..20 {
+            list.items.push(Item::new(ItemType::Todo, format!("item {}", i)));
+        }
+        list
+    }
+    #[test]
+    fn round_trip_keeps_every_field_and_duplicate_ids() {
+        let list = sample();
+        let mut storage = SqliteStorage::open_in_memory().unwrap();
+        storage.save(&list).unwrap();
+        storage.save(&list).unwrap();
+        assert_eq!(storage.load().unwrap().to_json().unwrap(), list.to_json().unwrap());
+        let mut copy = SqliteStorage::open_in_memory().unwrap();
+        copy.import_json(storage.export_json().unwrap()).unwrap();
+        assert_eq!(copy.export_json().unwrap(), list.to_json().unwrap());
+    }
+    #[test]
+    fn actions_only_touch_their_own_rows() {
+        let mut list = sample();
+        let mut storage = SqliteStorage::open_in_memory().unwrap();
+        storage.save(&list).unwrap();
+        let actions = [
+            (vec![5], ItemAction::Edit("renamed".to_string())),
+            (vec![1], ItemAction::Add(ItemType::Todo, "nested".to_string())),
+            (vec![1, 1], ItemAction::AddTag("deep".to_string())),
+            (vec![19], ItemAction::Remove),
+            (vec![1, 1], ItemAction::Remove),
+            (vec![], ItemAction::Add(ItemType::Note, "last".to_string())),
+        ];
+        for (path, action) in actions.into_iter() {
+            let before = total_changes(&storage);
+            let mut indices = path.iter().rev().copied().collect::<Vec<usize>>();
+            list.try_act_on_item_at(&mut indices, action.clone()).unwrap();
+            storage.apply_action(&list, &path, &action).unwrap();
+            assert!(total_changes(&storage) - before < 10);
+            assert_eq!(storage.load().unwrap().to_json().unwrap(), list.to_json().unwrap());
+        }
+    }
+    #[test]
+    fn item_edits_update_rows_without_rewriting_the_subtree() {
+        let mut list = sample();
+        let mut storage = SqliteStorage::open_in_memory().unwrap();
+        storage.save(&list).unwrap();
+        let actions = [
+            (vec![1], ItemAction::Edit("renamed".to_string()), 2),
+            (vec![1], ItemAction::AlterStatus(ItemStatus::InProgress), 2),
+            (vec![1, 1], ItemAction::SetPriority(Some(1)), 3),
+            (vec![1], ItemAction::SetField("owner".to_string(), Value::from("sam")), 3),
+            (vec![1], ItemAction::UnsetField("size".to_string()), 3),
+            (vec![1], ItemAction::AddTag("deep".to_string()), 7),
+        ];
+        for (path, action, changes) in actions.into_iter() {
+            let before = total_changes(&storage);
+            let mut indices = path.iter().rev().copied().collect::<Vec<usize>>();
+            list.try_act_on_item_at(&mut indices, action.clone()).unwrap();
+            storage.apply_action(&list, &path, &action).unwrap();
+            assert_eq!(total_changes(&storage) - before, changes);
+            assert_eq!(storage.load().unwrap().to_json().unwrap(), list.to_json().unwrap());
+        }
+    }
+    #[test]
+    fn container_applies_actions_without_rewriting() {
+        let path = temp_dir("sqlite-container").join("list.db");
+        let storage = SqliteStorage::open(&path).unwrap();
+        let mut container = Container::create_with("sqlite", Box::new(storage)).unwrap();
+        *container.list_mut() = sample();
+        container.list_mut().ensure_ids();
+        container.save().unwrap();
+        let actions = [
+            (vec![2], ItemAction::AlterStatus(ItemStatus::Complete)),
+            (vec![1], ItemAction::AlterStatus(ItemStatus::Complete)),
+            (vec![4], ItemAction::Remove),
+        ];
+        for (mut indices, action) in actions.into_iter() {
+            container.try_act_on_item_at(&mut indices, action).unwrap();
+        }
+        container.list_mut().items.swap(0, 1);
+        container.save().unwrap();
+        let reloaded = Container::load_with(
+            "sqlite", Box::new(SqliteStorage::open(&path).unwrap()),
+        ).unwrap();
+        assert_eq!(reloaded.list().to_json().unwrap(), container.list().to_json().unwrap());
+    }
+}
@@ -0,0 +1,207 @@
+use {
+    chrono::{ DateTime, Local, },
+    crate::{
+        enums::{ ItemStatus, ItemType, TableFormat, },
+        item::Item,
+        list::List,
+        utils::{ build_tree, get_printable_coords, },
+    },
+    std::{ collections::HashMap, str::FromStr, },
+};
+const COLUMNS: [&str; 8] = [
+    "path", "depth", "type", "status", "text", "created", "last_updated", "hidden",
+];
+fn needs_guard(value: &str) -> bool {
+    match value.strip_prefix('\'') {
+        Some(rest) => needs_guard(rest),
+        None => value.starts_with(['=', '+', '-', '@', '\t', '\r']),
+    }
+}
+fn guard(value: &str) -> String {
+    match needs_guard(value) {
+        true => format!("'{}", value),
+        false => value.to_string(),
+    }
+}
+fn unguard(value: &str) -> &str {
+    match value.strip_prefix('\'') {
+        Some(rest) if needs_guard(rest) => rest,
+        _ => value,
+    }
+}
+fn quote(value: &str, delimiter: char) -> String {
+    let value = guard(value);
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+fn export_rows(items: &[Item], parent: &[usize], format: TableFormat, output: &mut String) {
+    let delimiter = format.delimiter();
+    for (i, item) in items.iter().enumerate() {
+        let mut path = parent.to_vec();
+        path.push(i + 1);
+        let row = [
+            get_printable_coords(&path),
+            path.len().to_string(),
+            item.item_type.to_string(),
+            item.status.to_string(),
+            item.text.clone(),
+            item.created.to_rfc3339(),
+            item.last_updated.to_rfc3339(),
+            item.hidden.to_string(),
+        ];
+        let row = row.iter()
+            .map(|value| quote(value, delimiter))
+            .collect::<Vec<String>>();
+        output.push_str(&row.join(&delimiter.to_string()));
+        output.push('\n');
+        export_rows(&item.sub_items, &path, format, output);
+    }
+}
+pub fn export(list: &List, format: TableFormat) -> String {
+    let mut output = COLUMNS.join(&format.delimiter().to_string());
+    output.push('\n');
+    export_rows(&list.items, &[], format, &mut output);
+    output
+}
+fn parse_rows(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek().eq(&Some(&'"')) => {
+                chars.next();
+                field.push('"');
+            },
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            _ if quoted => field.push(c),
+            '\r' => {},
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            },
+            _ if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+    rows
+}
+fn parse_path(value: &str) -> Option<Vec<usize>> {
+    let path = value.split(',')
+        .map(|index| index.trim().parse::<usize>().ok().filter(|index| *index > 0))
+        .collect::<Option<Vec<usize>>>()?;
+    match path.is_empty() {
+        true => None,
+        false => Some(path),
+    }
+}
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value).ok()
+        .map(|time| time.with_timezone(&Local))
+}
+pub fn import(name: impl AsRef<str>, content: &str, format: TableFormat) -> List {
+    let mut list = List::new(name.as_ref().to_string());
+    let mut rows = parse_rows(content, format.delimiter()).into_iter();
+    let columns = match rows.next() {
+        Some(header) => header.iter()
+            .enumerate()
+            .map(|(i, column)| (column.trim().to_lowercase(), i))
+            .collect::<HashMap<String, usize>>(),
+        None => return list,
+    };
+    let mut paths = Vec::new();
+    let mut items = Vec::new();
+    for row in rows {
+        let cell = |column: &str| {
+            columns.get(column)
+                .and_then(|i| row.get(*i))
+                .map(|value| unguard(value))
+                .unwrap_or_default()
+        };
+        let path = match parse_path(cell("path")) {
+            Some(path) => path,
+            None => continue,
+        };
+        let item_type = ItemType::from_str(cell("type")).unwrap_or(ItemType::Todo);
+        let mut item = Item::new(item_type, cell("text"));
+        if let Ok(status) = ItemStatus::from_str(cell("status")) {
+            item.status = status;
+        }
+        if let Some(created) = parse_time(cell("created")) {
+            item.created = created;
+        }
+        if let Some(last_updated) = parse_time(cell("last_updated")) {
+            item.last_updated = last_updated;
+        }
+        item.hidden = cell("hidden").eq_ignore_ascii_case("true");
+        paths.push(path);
+        items.push(Some(item));
+    }
+    let by_path = paths.iter().enumerate()
+        .map(|(i, path)| (path.clone(), i))
+        .collect::<HashMap<Vec<usize>, usize>>();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        match by_path.get(&path[..path.len() - 1]) {
+            Some(parent) => children.entry(*parent).or_default().push(i),
+            None => roots.push(i),
+        }
+    }
+    roots.sort_by_key(|i| &paths[*i]);
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|i| paths[*i].last());
+    }
+    for i in roots {
+        if let Some(item) = build_tree(i, &mut items, &children) {
+            list.items.push(item);
+        }
+    }
+    list.define_missing_statuses();
+    list
+}
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_utils::texts,
+    };
+    #[test]
+    fn formulas_are_guarded_and_restored() {
+        let mut list = List::new("sheet".to_string());
+        for text in ["=SUM(A1)", "+1", "-x", "@cmd", "'=quoted", "'plain", "ok, \"fine\""] {
+            list.items.push(Item::new(ItemType::Todo, text));
+        }
+        let csv = export(&list, TableFormat::Csv);
+        assert!(csv.contains(",'=SUM(A1),") && csv.contains(",''=quoted,"));
+        assert!(csv.contains(",'plain,"));
+        for format in [TableFormat::Csv, TableFormat::Tsv] {
+            let imported = import("sheet", &export(&list, format), format);
+            assert_eq!(texts(&imported.items), texts(&list.items));
+        }
+    }
+    #[test]
+    fn rows_are_placed_by_path_not_row_order() {
+        let csv = "path,type,status,text\n\
+            2,todo,incomplete,second\n\
+            \"1,2\",note,incomplete,child b\n\
+            1,todo,complete,first\n\
+            \"1,1\",todo,in-progress,child a\n";
+        let list = import("sheet", csv, TableFormat::Csv);
+        assert_eq!(texts(&list.items), vec!["first", "second"]);
+        assert_eq!(texts(&list.items[0].sub_items), vec!["child a", "child b"]);
+        assert!(list.items[0].status.eq(&ItemStatus::Complete));
+        assert!(list.items[0].sub_items[1].item_type.eq(&ItemType::Note));
+    }
+}
@@ -0,0 +1,22 @@
+use {
+    crate::{
+        enums::{ ItemStatus, ItemType, },
+        item::Item,
+    },
+    std::{ fs, path::PathBuf, process, },
+};
+pub fn todo(text: &str, status: ItemStatus) -> Item {
+    let mut item = Item::new(ItemType::Todo, text);
+    item.status = status;
+    item
+}
+pub fn texts(items: &[Item]) -> Vec<&str> {
+    items.iter().map(|item| item.text.as_str()).collect()
+}
+pub fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("todo-core-{}-{}", test, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
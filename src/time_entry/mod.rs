@@ -0,0 +1,26 @@
+use {
+    chrono::{ DateTime, Duration, Local, },
+    serde::{ Deserialize, Serialize, },
+};
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct TimeEntry {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+impl TimeEntry {
+    pub fn new(note: Option<String>) -> Self {
+        Self {
+            start: Local::now(),
+            end: None,
+            note,
+        }
+    }
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+    pub fn duration(&self) -> Duration {
+        self.end.unwrap_or_else(Local::now) - self.start
+    }
+}
@@ -0,0 +1,334 @@
+use {
+    chrono::NaiveDate,
+    crate::{
+        enums::{ Estimate, ItemStatus, ItemType, Recurrence, TodoTxtNesting, },
+        item::Item,
+        list::List,
+        utils::{ build_tree, date_to_local, letter_priority, priority_letter, },
+    },
+    serde_json::Value,
+    std::{ collections::HashMap, str::FromStr, },
+};
+fn estimate_token(estimate: &Estimate) -> String {
+    match estimate {
+        Estimate::Points(points) => format!("{}pt", points),
+        Estimate::Minutes(minutes) => format!("{}m", minutes),
+    }
+}
+fn is_word(value: &str) -> bool {
+    !value.is_empty() && !value.contains(char::is_whitespace)
+}
+fn is_extra_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic())
+}
+fn is_extra_value(value: &str) -> bool {
+    is_word(value) && !value.starts_with("//")
+}
+fn export_line(item: &Item, parent: Option<&Item>, level: usize, nesting: TodoTxtNesting) -> String {
+    let mut tokens = Vec::new();
+    let done = item.status.eq(&ItemStatus::Complete);
+    if done {
+        tokens.push("x".to_string());
+        tokens.push(item.last_updated.format("%Y-%m-%d").to_string());
+    } else if let Some(priority) = item.priority {
+        tokens.push(format!("({})", priority_letter(priority)));
+    }
+    tokens.push(item.created.format("%Y-%m-%d").to_string());
+    tokens.push(item.text.clone());
+    for tag in item.tags.iter().filter(|tag| is_word(tag)) {
+        if tag.starts_with('@') || tag.starts_with('+') {
+            tokens.push(tag.clone());
+        } else {
+            tokens.push(format!("+{}", tag));
+        }
+    }
+    if done {
+        if let Some(priority) = item.priority {
+            tokens.push(format!("pri:{}", priority_letter(priority)));
+        }
+    }
+    if item.item_type.ne(&ItemType::Todo) {
+        tokens.push(format!("type:{}", item.item_type));
+    }
+    if !done && item.status.ne(&ItemStatus::Incomplete) {
+        tokens.push(format!("status:{}", item.status));
+    }
+    if let Some(due) = item.due {
+        tokens.push(format!("due:{}", due.format("%Y-%m-%d")));
+    }
+    if let Some(recurrence) = &item.recurrence {
+        tokens.push(format!("rec:{}", recurrence));
+    }
+    if let Some(estimate) = &item.estimate {
+        tokens.push(format!("est:{}", estimate_token(estimate)));
+    }
+    if let Some(link) = item.link.as_ref().filter(|link| is_word(link)) {
+        tokens.push(format!("link:{}", link));
+    }
+    for assignee in item.assignees.iter().filter(|assignee| is_word(assignee)) {
+        tokens.push(format!("owner:{}", assignee));
+    }
+    if !item.depends_on.is_empty() {
+        tokens.push(format!("dep:{}", item.depends_on.join(",")));
+    }
+    if item.hidden {
+        tokens.push("h:1".to_string());
+    }
+    for key in item.fields.keys().filter(|key| is_extra_key(key)) {
+        if let Some(value) = item.field_text(key).filter(|value| is_extra_value(value)) {
+            tokens.push(format!("{}:{}", key, value));
+        }
+    }
+    let mut line = String::new();
+    match nesting {
+        TodoTxtNesting::Flatten => {},
+        TodoTxtNesting::ParentKey => {
+            tokens.push(format!("id:{}", item.id));
+            if let Some(parent) = parent {
+                tokens.push(format!("parent:{}", parent.id));
+            }
+        },
+        TodoTxtNesting::Indent => line.push_str(&"  ".repeat(level)),
+    }
+    line.push_str(&tokens.join(" "));
+    line
+}
+fn export_items(
+    items: &[Item], parent: Option<&Item>, level: usize, nesting: TodoTxtNesting,
+    lines: &mut Vec<String>,
+) {
+    for item in items.iter() {
+        lines.push(export_line(item, parent, level, nesting));
+        export_items(&item.sub_items, Some(item), level + 1, nesting, lines);
+    }
+}
+pub fn export(list: &List, nesting: TodoTxtNesting) -> String {
+    let mut lines = Vec::new();
+    export_items(&list.items, None, 0, nesting, &mut lines);
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()
+}
+fn parse_priority(token: &str) -> Option<u8> {
+    letter_priority(token.strip_prefix('(')?.strip_suffix(')')?)
+}
+fn split_extra(token: &str) -> Option<(&str, &str)> {
+    let (key, value) = token.split_once(':')?;
+    if !is_extra_key(key) || !is_extra_value(value) {
+        return None;
+    }
+    Some((key, value))
+}
+struct ImportedLine {
+    item: Item,
+    level: usize,
+    id: Option<String>,
+    parent: Option<String>,
+}
+fn import_line(line: &str) -> ImportedLine {
+    let trimmed = line.trim_start();
+    let level = (line.len() - trimmed.len()) / 2;
+    let mut tokens = trimmed.split_whitespace().peekable();
+    let mut item = Item::new(ItemType::Todo, "");
+    let mut id = None;
+    let mut parent = None;
+    if tokens.peek().eq(&Some(&"x")) {
+        tokens.next();
+        item.status = ItemStatus::Complete;
+        if let Some(completed) = tokens.peek().and_then(|token| parse_date(token)) {
+            tokens.next();
+            item.last_updated = date_to_local(completed);
+        }
+    } else if let Some(priority) = tokens.peek().and_then(|token| parse_priority(token)) {
+        tokens.next();
+        item.priority = Some(priority);
+    }
+    if let Some(created) = tokens.peek().and_then(|token| parse_date(token)) {
+        tokens.next();
+        item.created = date_to_local(created);
+        if item.status.ne(&ItemStatus::Complete) {
+            item.last_updated = item.created;
+        }
+    }
+    let mut text = Vec::new();
+    for token in tokens {
+        if token.len() > 1 && token.starts_with('+') {
+            item.tags.push(token[1..].to_string());
+            continue;
+        }
+        if token.len() > 1 && token.starts_with('@') {
+            item.tags.push(token.to_string());
+            continue;
+        }
+        let (key, value) = match split_extra(token) {
+            Some(extra) => extra,
+            None => {
+                text.push(token);
+                continue;
+            },
+        };
+        match key {
+            "pri" => item.priority = parse_priority(&format!("({})", value)),
+            "type" => {
+                if let Ok(item_type) = ItemType::from_str(value) {
+                    item.item_type = item_type;
+                }
+            },
+            "status" => {
+                if let Ok(status) = ItemStatus::from_str(value) {
+                    item.status = status;
+                }
+            },
+            "due" => item.due = parse_date(value).map(date_to_local),
+            "rec" => item.recurrence = Recurrence::from_str(value).ok(),
+            "est" => item.estimate = Estimate::from_str(value).ok(),
+            "link" => item.link = Some(value.to_string()),
+            "owner" => item.assignees.push(value.to_string()),
+            "dep" => {
+                item.depends_on.extend(value.split(',').map(|dep| dep.to_string()));
+            },
+            "h" => item.hidden = value.eq("1"),
+            "id" => id = Some(value.to_string()),
+            "parent" => parent = Some(value.to_string()),
+            _ => {
+                item.fields.insert(key.to_string(), Value::String(value.to_string()));
+            },
+        }
+    }
+    item.text = text.join(" ");
+    if let Some(id) = &id {
+        item.id = id.clone();
+    }
+    ImportedLine { item, level, id, parent, }
+}
+pub fn import(name: impl AsRef<str>, content: &str) -> List {
+    let lines = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(import_line)
+        .collect::<Vec<ImportedLine>>();
+    let by_id = lines.iter().enumerate()
+        .filter_map(|(i, line)| line.id.clone().map(|id| (id, i)))
+        .collect::<HashMap<String, usize>>();
+    let mut parents = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        while stack.last().is_some_and(|(level, _)| *level >= line.level) {
+            stack.pop();
+        }
+        let parent = match line.parent.as_ref().and_then(|parent| by_id.get(parent)) {
+            Some(parent) if parent.ne(&i) => Some(*parent),
+            _ => stack.last().map(|(_, parent)| *parent),
+        };
+        parents.push(parent);
+        stack.push((line.level, i));
+    }
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, parent) in parents.iter().enumerate() {
+        if let Some(parent) = parent {
+            children.entry(*parent).or_default().push(i);
+        }
+    }
+    let mut items = lines.into_iter()
+        .map(|line| Some(line.item))
+        .collect::<Vec<Option<Item>>>();
+    let mut list = List::new(name.as_ref().to_string());
+    for (i, parent) in parents.iter().enumerate() {
+        if parent.is_none() {
+            if let Some(item) = build_tree(i, &mut items, &children) {
+                list.items.push(item);
+            }
+        }
+    }
+    for i in 0..items.len() {
+        if let Some(item) = build_tree(i, &mut items, &children) {
+            list.items.push(item);
+        }
+    }
+    list.ensure_ids();
+    list.define_missing_statuses();
+    list
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn sample() -> List {
+        let mut list = List::new("todo.txt".to_string());
+        let mut parent = Item::new(ItemType::Todo, "Plan trip");
+        parent.priority = Some(1);
+        parent.tags = vec!["travel".to_string(), "@phone".to_string()];
+        parent.due = Some(date_to_local(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()));
+        parent.estimate = Some(Estimate::Minutes(90));
+        let mut child = Item::new(ItemType::Note, "Pack bags");
+        child.status = ItemStatus::Complete;
+        child.hidden = true;
+        parent.sub_items.push(child);
+        list.items.push(parent);
+        list.items.push(Item::new(ItemType::Todo, "Call home"));
+        list
+    }
+    #[test]
+    fn imports_the_standard_line_layout() {
+        let content = "(A) 2024-01-02 Call mom +family @phone due:2024-01-05 size:big\n\
+            x 2024-01-04 2024-01-01 Buy milk pri:B\n";
+        let list = import("todo.txt", content);
+        assert_eq!(list.items.len(), 2);
+        let call = &list.items[0];
+        assert_eq!(call.text, "Call mom");
+        assert_eq!(call.priority, Some(1));
+        assert_eq!(call.tags, vec!["family", "@phone"]);
+        assert_eq!(call.created.date_naive(), NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert!(call.due.is_some());
+        assert_eq!(call.field_text("size").as_deref(), Some("big"));
+        let milk = &list.items[1];
+        assert!(milk.status.eq(&ItemStatus::Complete));
+        assert_eq!(milk.priority, Some(2));
+        assert_eq!(milk.last_updated.date_naive(), NaiveDate::from_ymd_opt(2024, 1, 4).unwrap());
+    }
+    #[test]
+    fn nesting_modes_round_trip_the_tree() {
+        let list = sample();
+        for nesting in [TodoTxtNesting::ParentKey, TodoTxtNesting::Indent] {
+            let imported = import("todo.txt", &export(&list, nesting));
+            assert_eq!(imported.items.len(), 2);
+            let parent = &imported.items[0];
+            assert_eq!(parent.text, "Plan trip");
+            assert_eq!(parent.priority, Some(1));
+            assert_eq!(parent.tags, vec!["travel", "@phone"]);
+            assert!(parent.estimate.eq(&Some(Estimate::Minutes(90))));
+            let child = &parent.sub_items[0];
+            assert!(child.item_type.eq(&ItemType::Note));
+            assert!(child.status.eq(&ItemStatus::Complete));
+            assert!(child.hidden);
+        }
+        let flat = import("todo.txt", &export(&list, TodoTxtNesting::Flatten));
+        assert_eq!(flat.items.len(), 3);
+    }
+    #[test]
+    fn times_and_urls_stay_in_the_text() {
+        let mut list = List::new("todo.txt".to_string());
+        let text = "Call at 10:30 about http://example.com/a and ftp://host:21";
+        let mut item = Item::new(ItemType::Todo, text);
+        item.fields.insert("room".to_string(), Value::from("4b"));
+        item.fields.insert("slot_2".to_string(), Value::from("x"));
+        item.fields.insert("url".to_string(), Value::from("//host"));
+        list.items.push(item);
+        let line = export(&list, TodoTxtNesting::Flatten);
+        assert!(line.contains("room:4b") && !line.contains("slot_2") && !line.contains("url:"));
+        let imported = import("todo.txt", &line);
+        let item = &imported.items[0];
+        assert_eq!(item.text, text);
+        assert_eq!(item.field_text("room").as_deref(), Some("4b"));
+        assert_eq!(item.fields.len(), 1);
+    }
+    #[test]
+    fn custom_statuses_are_defined_on_import() {
+        let list = import("todo.txt", "2024-01-01 Review status:review\n");
+        assert!(list.items[0].status.eq(&ItemStatus::Custom("review".to_string())));
+        assert!(list.validate_statuses().is_ok());
+    }
+}
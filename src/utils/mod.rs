@@ -1,8 +1,59 @@
+use {
+    chrono::{ DateTime, Duration, Local, NaiveDate, TimeZone, },
+    crate::item::Item,
+    std::{
+        collections::HashMap,
+        env,
+        sync::atomic::{ AtomicU64, Ordering, },
+        time::{ SystemTime, UNIX_EPOCH, },
+    },
+};
+static LAST_ID: AtomicU64 = AtomicU64::new(0);
+#[allow(clippy::ptr_arg)]
 pub fn get_printable_coords(nums: &Vec<usize>) -> String {
-    nums.into_iter().map(|num| {
+    nums.iter().map(|num| {
         num.to_string()
     }).collect::<Vec<String>>().join(", ").to_string()
 }
+pub fn format_duration(duration: &Duration) -> String {
+    let minutes = duration.num_minutes();
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+pub fn date_to_local(date: NaiveDate) -> DateTime<Local> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    match Local.from_local_datetime(&midnight).earliest() {
+        Some(local) => local,
+        None => Local.from_utc_datetime(&midnight),
+    }
+}
+pub fn priority_letter(priority: u8) -> char {
+    (b'A' + priority.saturating_sub(1).min(25)) as char
+}
+pub fn letter_priority(letter: &str) -> Option<u8> {
+    match letter.as_bytes() {
+        [c] if c.is_ascii_uppercase() => Some(c - b'A' + 1),
+        _ => None,
+    }
+}
+pub fn current_user() -> Option<String> {
+    ["TODO_USER", "USER"].iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|user| !user.trim().is_empty())
+}
+pub fn generate_id() -> String {
+    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_nanos() as u64,
+        Err(_) => 0,
+    };
+    let mut last = LAST_ID.load(Ordering::SeqCst);
+    loop {
+        let next = if now > last { now } else { last + 1 };
+        match LAST_ID.compare_exchange(last, next, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(_) => return format!("{:x}", next),
+            Err(actual) => last = actual,
+        }
+    }
+}
 pub mod styler {
     use crossterm::style::{
         Attribute,
@@ -24,6 +75,18 @@ pub mod styler {
     pub fn danger(msg: impl AsRef<str>) -> String {
         format!("{}", msg.as_ref().with(Color::Red))
     }
+    pub fn accent(msg: impl AsRef<str>) -> String {
+        format!("{}", msg.as_ref().with(Color::Magenta))
+    }
+    pub fn muted(msg: impl AsRef<str>) -> String {
+        format!("{}", msg.as_ref().with(Color::DarkGrey))
+    }
+    pub fn color(msg: impl AsRef<str>, color: &str) -> Option<String> {
+        match Color::try_from(color) {
+            Ok(color) => Some(format!("{}", msg.as_ref().with(color))),
+            Err(_) => None,
+        }
+    }
     pub fn bold(msg: impl AsRef<str>) -> String {
         format!("{}", msg.as_ref().attribute(Attribute::Bold))
     }
@@ -31,3 +94,42 @@ pub mod styler {
         format!("{}", msg.as_ref().attribute(Attribute::Italic))
     }
 }
+#[derive(Clone, Copy)]
+pub enum OutlineLevel {
+    Heading(usize),
+    ListItem(usize),
+}
+impl OutlineLevel {
+    fn closes(&self, open: &Self) -> bool {
+        match (self, open) {
+            (Self::Heading(depth), Self::Heading(top)) => top >= depth,
+            (Self::Heading(_), Self::ListItem(_)) => true,
+            (Self::ListItem(_), Self::Heading(_)) => false,
+            (Self::ListItem(indent), Self::ListItem(top)) => top >= indent,
+        }
+    }
+}
+pub fn collapse_outline(
+    stack: &mut Vec<(OutlineLevel, Item)>, roots: &mut Vec<Item>, level: OutlineLevel,
+) {
+    while stack.last().is_some_and(|(top, _)| level.closes(top)) {
+        let (_, item) = stack.pop().unwrap();
+        match stack.last_mut() {
+            Some((_, parent)) => parent.sub_items.push(item),
+            None => roots.push(item),
+        }
+    }
+}
+pub fn build_tree(
+    index: usize, items: &mut Vec<Option<Item>>, children: &HashMap<usize, Vec<usize>>,
+) -> Option<Item> {
+    let mut item = items[index].take()?;
+    if let Some(child_indices) = children.get(&index) {
+        for child in child_indices.iter() {
+            if let Some(sub) = build_tree(*child, items, children) {
+                item.sub_items.push(sub);
+            }
+        }
+    }
+    Some(item)
+}